@@ -0,0 +1,181 @@
+// Simulation throughput on generated projects of 1,000 and 10,000 tasks,
+// against the engine that looked tasks up by id in each iteration
+//
+//     cargo bench --bench simulation
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use distimate::Pert;
+use mcps::{
+    schedule::Project,
+    simulation::{run_multiple_simulations_with_options, SimulationOptions, SimulationResult},
+    task::{days_to_duration, Task},
+};
+use rand::prelude::*;
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+const ITERATIONS: usize = 100;
+
+// A layered project: each task depends on up to three tasks from earlier
+// layers, the way large plans tend to fan out and back in
+fn generate_project(num_tasks: usize) -> Project {
+    let mut rng = StdRng::seed_from_u64(num_tasks as u64);
+    let layer_size = 50;
+    let tasks = (0..num_tasks)
+        .map(|i| {
+            let earlier = i - i % layer_size;
+            let dependencies = if earlier == 0 {
+                vec![]
+            } else {
+                let count = rng.gen_range(1..=3);
+                (0..count)
+                    .map(|_| format!("T{}", rng.gen_range(0..earlier)))
+                    .collect::<std::collections::BTreeSet<_>>()
+                    .into_iter()
+                    .collect()
+            };
+            let likely = rng.gen_range(1.0..10.0);
+            Task::new(
+                &format!("T{}", i),
+                dependencies,
+                days_to_duration(likely * 0.5),
+                days_to_duration(likely),
+                days_to_duration(likely * 2.0),
+            )
+        })
+        .collect();
+    Project::new(tasks, 10, None).unwrap()
+}
+
+// The engine before the dependency graph was compiled once per run, kept
+// only to compare against. It rebuilds its maps from task ids each iteration.
+fn baseline_simulation(schedule: &Project, rng: &mut impl Rng) -> SimulationResult {
+    let task_effort_times: HashMap<_, _> = schedule
+        .tasks
+        .iter()
+        .map(|task| {
+            let (min, max) = (task.min_time.as_secs_f64(), task.max_time.as_secs_f64());
+            let pert = Pert::new(min, (min + max) / 2.0, max).unwrap();
+            (&task.id, Duration::from_secs_f64(pert.sample(rng)))
+        })
+        .collect();
+    let total_effort_time: Duration = task_effort_times.values().sum();
+
+    let mut task_dependencies: HashMap<_, HashSet<_>> = HashMap::new();
+    let mut reverse_dependencies: HashMap<_, Vec<_>> = HashMap::new();
+    for task in &schedule.tasks {
+        task_dependencies.insert(&task.id, task.dependencies.iter().collect());
+        for dep in &task.dependencies {
+            reverse_dependencies.entry(dep).or_default().push(&task.id);
+        }
+    }
+
+    let mut task_queue: Vec<_> = schedule
+        .tasks
+        .iter()
+        .filter(|t| t.dependencies.is_empty())
+        .map(|t| &t.id)
+        .collect();
+    let mut current_time = Duration::default();
+    let mut completed_tasks = HashSet::new();
+    let mut task_start_times = HashMap::new();
+    let mut task_finish_times = HashMap::new();
+    let mut worker_finish_times = vec![Duration::default(); schedule.num_workers];
+    let mut worker_tasks: Vec<Option<&String>> = vec![None; schedule.num_workers];
+
+    while completed_tasks.len() < schedule.tasks.len() {
+        for worker in 0..schedule.num_workers {
+            if task_queue.is_empty() {
+                break;
+            }
+            if worker_tasks[worker].is_some() {
+                continue;
+            }
+            let task_id = task_queue.swap_remove(rng.gen_range(0..task_queue.len()));
+            worker_finish_times[worker] = current_time + task_effort_times[task_id];
+            worker_tasks[worker] = Some(task_id);
+            task_start_times.insert(task_id, current_time);
+        }
+
+        current_time = worker_tasks
+            .iter()
+            .zip(&worker_finish_times)
+            .filter(|(task, _)| task.is_some())
+            .map(|(_, &time)| time)
+            .min()
+            .unwrap_or(current_time);
+
+        for worker in 0..schedule.num_workers {
+            if worker_finish_times[worker] != current_time {
+                continue;
+            }
+            let Some(task_id) = worker_tasks[worker].take() else {
+                continue;
+            };
+            completed_tasks.insert(task_id);
+            task_finish_times.insert(task_id, current_time);
+
+            for &dep_task in reverse_dependencies.get(task_id).into_iter().flatten() {
+                if !completed_tasks.contains(dep_task)
+                    && !task_queue.contains(&dep_task)
+                    && task_dependencies[dep_task]
+                        .iter()
+                        .all(|dep| completed_tasks.contains(dep))
+                {
+                    task_queue.push(dep_task);
+                }
+            }
+        }
+    }
+
+    let by_task =
+        |times: &HashMap<&String, Duration>| schedule.tasks.iter().map(|t| times[&t.id]).collect();
+    SimulationResult {
+        total_project_duration: current_time,
+        total_effort_time,
+        task_start_times: by_task(&task_start_times),
+        task_finish_times: by_task(&task_finish_times),
+        task_effort_times: by_task(&task_effort_times),
+    }
+}
+
+fn bench_simulation(c: &mut Criterion) {
+    // One thread, to compare like with like against the baseline
+    let options = SimulationOptions {
+        seed: Some(1),
+        threads: Some(1),
+        ..Default::default()
+    };
+    let mut group = c.benchmark_group("simulation");
+    group.sample_size(10);
+    for num_tasks in [1_000, 10_000] {
+        let project = generate_project(num_tasks);
+        group.bench_with_input(
+            BenchmarkId::new("run_multiple_simulations", num_tasks),
+            &project,
+            |b, project| {
+                b.iter(|| {
+                    run_multiple_simulations_with_options(project, ITERATIONS, &options).unwrap()
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("baseline", num_tasks),
+            &project,
+            |b, project| {
+                let mut rng = StdRng::seed_from_u64(1);
+                b.iter(|| {
+                    for _ in 0..ITERATIONS {
+                        black_box(baseline_simulation(project, &mut rng));
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_simulation);
+criterion_main!(benches);
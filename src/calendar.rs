@@ -0,0 +1,599 @@
+// Working days and holidays, read from the work schedule config file given
+// with --schedule
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::Deserialize;
+
+use std::{collections::BTreeSet, str::FromStr};
+
+// Regional holiday sets bundled with mcps, by name
+const HOLIDAY_SETS: [(&str, &str); 3] = [
+    ("de", include_str!("holidays/de.yaml")),
+    ("uk", include_str!("holidays/uk.yaml")),
+    ("us", include_str!("holidays/us.yaml")),
+];
+
+pub const DEFAULT_HOURS_PER_DAY: f64 = 8.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Calendar {
+    // Indexed by days from Monday
+    work_days: [bool; 7],
+    holidays: DaySet,
+    // Days with half the usual working hours
+    half_days: DaySet,
+    // Working hours in a full working day
+    hours_per_day: f64,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Calendar::new()
+    }
+}
+
+// Particular dates plus rules for days that come round every year
+#[derive(Debug, Clone, Default, PartialEq)]
+struct DaySet {
+    dates: BTreeSet<NaiveDate>,
+    yearly: Vec<YearlyDay>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum YearlyDay {
+    // Month and day, e.g. (12, 25)
+    Date(u32, u32),
+    // From and to, inclusive, wrapping round the new year if to is earlier
+    Range((u32, u32), (u32, u32)),
+    // The nth weekday of the month, counting back from the end when negative
+    NthWeekday(u32, Weekday, i32),
+    // Days after Easter Sunday
+    Easter(i64),
+}
+
+impl DaySet {
+    fn contains(&self, date: NaiveDate) -> bool {
+        self.dates.contains(&date) || self.yearly.iter().any(|day| day.falls_on(date))
+    }
+
+    fn add(&mut self, input: DayInput) -> Result<(), String> {
+        match input {
+            DayInput::Date(date) | DayInput::Named { date } => match parse_day(&date)? {
+                Day::Date(date) => {
+                    self.dates.insert(date);
+                }
+                Day::Yearly(month, day) => self.yearly.push(YearlyDay::Date(month, day)),
+            },
+            DayInput::Range { from, to } => match (parse_day(&from)?, parse_day(&to)?) {
+                (Day::Date(first), Day::Date(last)) => {
+                    if last < first {
+                        return Err(format!(
+                            "Range from {} to {} ends before it starts",
+                            from, to
+                        ));
+                    }
+                    self.dates
+                        .extend(first.iter_days().take_while(|&date| date <= last));
+                }
+                (Day::Yearly(m1, d1), Day::Yearly(m2, d2)) => {
+                    self.yearly.push(YearlyDay::Range((m1, d1), (m2, d2)));
+                }
+                _ => {
+                    return Err(format!(
+                        "Range from {} to {} must give both years or neither",
+                        from, to
+                    ))
+                }
+            },
+            DayInput::NthWeekday {
+                month,
+                weekday,
+                nth,
+            } => {
+                let day = parse_weekday(&weekday)
+                    .ok_or_else(|| format!("Unknown day of the week '{}'", weekday))?;
+                if !(1..=12).contains(&month) || !(1..=5).contains(&nth.abs()) {
+                    return Err(format!("Invalid month {} or nth {}", month, nth));
+                }
+                self.yearly.push(YearlyDay::NthWeekday(month, day, nth));
+            }
+            DayInput::Easter { easter } => self.yearly.push(YearlyDay::Easter(easter)),
+        }
+        Ok(())
+    }
+}
+
+impl YearlyDay {
+    fn falls_on(&self, date: NaiveDate) -> bool {
+        let day = (date.month(), date.day());
+        match *self {
+            YearlyDay::Date(month, dom) => day == (month, dom),
+            YearlyDay::Range(from, to) if from <= to => from <= day && day <= to,
+            YearlyDay::Range(from, to) => from <= day || day <= to,
+            YearlyDay::NthWeekday(month, weekday, nth) => {
+                if date.month() != month || date.weekday() != weekday {
+                    false
+                } else if nth > 0 {
+                    (date.day0() / 7 + 1) as i32 == nth
+                } else {
+                    let days_left = days_in_month(date) - date.day();
+                    (days_left / 7 + 1) as i32 == -nth
+                }
+            }
+            YearlyDay::Easter(offset) => easter_sunday(date.year())
+                .and_then(|easter| easter.checked_add_signed(chrono::Duration::days(offset)))
+                .is_some_and(|holiday| holiday == date),
+        }
+    }
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|first| first.pred_opt())
+        .map_or(31, |last| last.day())
+}
+
+// Easter Sunday in the Gregorian calendar (the anonymous Gregorian algorithm)
+fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+enum Day {
+    Date(NaiveDate),
+    // Month and day, every year
+    Yearly(u32, u32),
+}
+
+// `2024-12-25` is that date, `12-25` is every 25th of December
+fn parse_day(day: &str) -> Result<Day, String> {
+    let invalid = || format!("Invalid date '{}'", day);
+    if let Ok(date) = NaiveDate::parse_from_str(day, "%Y-%m-%d") {
+        return Ok(Day::Date(date));
+    }
+    let (month, dom) = day.split_once('-').ok_or_else(invalid)?;
+    let month: u32 = month.parse().map_err(|_| invalid())?;
+    let dom: u32 = dom.parse().map_err(|_| invalid())?;
+    // A leap year, so that 02-29 is allowed
+    NaiveDate::from_ymd_opt(2000, month, dom).ok_or_else(invalid)?;
+    Ok(Day::Yearly(month, dom))
+}
+
+// A holiday or half day, e.g. `2024-12-25`, `12-25` (every year),
+// `{ from: 12-24, to: 01-02 }`, `{ month: 11, weekday: Thu, nth: 4 }` (the
+// fourth Thursday in November, -1 for the last) or `{ easter: 1 }` (days
+// after Easter Sunday). Any `name` is only for the reader.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum DayInput {
+    Date(String),
+    Named {
+        date: String,
+    },
+    Range {
+        from: String,
+        to: String,
+    },
+    NthWeekday {
+        month: u32,
+        weekday: String,
+        nth: i32,
+    },
+    Easter {
+        easter: i64,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct CalendarInput {
+    work_days: Option<Vec<String>>,
+    #[serde(default)]
+    holidays: Vec<DayInput>,
+    // Names of bundled regional holiday sets
+    #[serde(default)]
+    holiday_sets: Vec<String>,
+    #[serde(default)]
+    half_days: Vec<DayInput>,
+    hours_per_day: Option<f64>,
+}
+
+// Reads YAML or JSON, e.g. `work_days: [Mon, Tue, Wed]`,
+// `holidays: [2024-12-25, 01-01]`, `holiday_sets: [us]`,
+// `half_days: [12-24]` and `hours_per_day: 7.5`
+impl FromStr for Calendar {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input: CalendarInput = if s.trim_start().starts_with('{') {
+            serde_json::from_str(s)?
+        } else {
+            serde_yaml::from_str(s)?
+        };
+        let mut calendar = Calendar::new();
+        if let Some(days) = input.work_days {
+            let days = parse_weekdays(&days)?;
+            if days.is_empty() {
+                return Err("No work days defined".into());
+            }
+            calendar.set_work_days(&days);
+        }
+        for name in input.holiday_sets {
+            calendar.add_holiday_set(&name)?;
+        }
+        for day in input.holidays {
+            calendar.holidays.add(day)?;
+        }
+        for day in input.half_days {
+            calendar.half_days.add(day)?;
+        }
+        if let Some(hours) = input.hours_per_day {
+            if !(hours > 0.0 && hours <= 24.0) {
+                return Err("Hours per day must be above 0 and at most 24".into());
+            }
+            calendar.hours_per_day = hours;
+        }
+        if !calendar.has_working_days() {
+            return Err("No working days left after holidays".into());
+        }
+        Ok(calendar)
+    }
+}
+
+impl Calendar {
+    // Monday to Friday, 8 hours a day, with no holidays
+    pub fn new() -> Self {
+        Calendar {
+            work_days: [true, true, true, true, true, false, false],
+            holidays: DaySet::default(),
+            half_days: DaySet::default(),
+            hours_per_day: DEFAULT_HOURS_PER_DAY,
+        }
+    }
+
+    pub fn set_work_days(&mut self, days: &[Weekday]) {
+        self.work_days = [false; 7];
+        for day in days {
+            self.work_days[day.num_days_from_monday() as usize] = true;
+        }
+    }
+
+    pub fn add_holiday(&mut self, date: NaiveDate) {
+        self.holidays.dates.insert(date);
+    }
+
+    // Adds the holidays of a bundled regional set, e.g. "us"
+    pub fn add_holiday_set(&mut self, name: &str) -> Result<(), String> {
+        let (_, data) = HOLIDAY_SETS
+            .iter()
+            .find(|(set, _)| set.eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                let names: Vec<_> = HOLIDAY_SETS.iter().map(|(set, _)| *set).collect();
+                format!(
+                    "Unknown holiday set '{}', expected one of: {}",
+                    name,
+                    names.join(", ")
+                )
+            })?;
+        let input: CalendarInput = serde_yaml::from_str(data).map_err(|e| e.to_string())?;
+        for day in input.holidays {
+            self.holidays.add(day)?;
+        }
+        Ok(())
+    }
+
+    pub fn add_half_day(&mut self, date: NaiveDate) {
+        self.half_days.dates.insert(date);
+    }
+
+    pub fn hours_per_day(&self) -> f64 {
+        self.hours_per_day
+    }
+
+    pub fn work_days_per_week(&self) -> usize {
+        self.work_days.iter().filter(|&&works| works).count()
+    }
+
+    pub fn is_work_day(&self, day: Weekday) -> bool {
+        self.work_days[day.num_days_from_monday() as usize]
+    }
+
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.holidays.contains(date)
+    }
+
+    pub fn is_half_day(&self, date: NaiveDate) -> bool {
+        self.half_days.contains(date)
+    }
+
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        self.is_work_day(date.weekday()) && !self.is_holiday(date)
+    }
+
+    // Whether yearly holidays leave any working day in a year. Checked after
+    // the last dated holiday, as those run out.
+    fn has_working_days(&self) -> bool {
+        let start = self
+            .holidays
+            .dates
+            .last()
+            .and_then(|last| last.succ_opt())
+            .unwrap_or_default();
+        start
+            .iter_days()
+            .take(366)
+            .any(|date| self.is_working_day(date))
+    }
+
+    // Share of a full working day worked on `date`
+    pub fn day_length(&self, date: NaiveDate) -> f64 {
+        if !self.is_working_day(date) {
+            0.0
+        } else if self.is_half_day(date) {
+            0.5
+        } else {
+            1.0
+        }
+    }
+
+    // The date on which `days_worked` working days are done, counting `start`
+    // if it's a working day, and the number of calendar days after `start`
+    // that is
+    pub fn compute_end_date(
+        &self,
+        start: NaiveDate,
+        days_worked: i64,
+    ) -> Result<(NaiveDate, chrono::Duration), String> {
+        if days_worked < 0 {
+            return Err("Number of days worked must not be negative".to_string());
+        }
+        if !self.work_days.contains(&true) {
+            return Err("No work days defined".to_string());
+        }
+
+        let mut date = start;
+        let mut remaining = days_worked;
+        if self.is_working_day(date) {
+            remaining -= 1;
+        }
+        while remaining > 0 {
+            date = date.succ_opt().ok_or("Date out of range")?;
+            if self.is_working_day(date) {
+                remaining -= 1;
+            }
+        }
+        Ok((date, date.signed_duration_since(start)))
+    }
+
+    // The working day, from `start` on, by which `days` of working time have
+    // passed. With `finishing` that's the day that much work is done on, so a
+    // whole number of days ends a day; otherwise it's the day work carries on
+    // after it.
+    pub fn date_after(
+        &self,
+        start: NaiveDate,
+        days: f64,
+        finishing: bool,
+    ) -> Result<NaiveDate, String> {
+        if !self.work_days.contains(&true) {
+            return Err("No work days defined".to_string());
+        }
+        let mut before = 0.0;
+        for date in start.iter_days() {
+            let length = self.day_length(date);
+            let after = before + length;
+            if length > 0.0 && (after > days || (finishing && after >= days)) {
+                return Ok(date);
+            }
+            before = after;
+        }
+        Err("Date out of range".to_string())
+    }
+}
+
+// Accepts full and three letter day names, in any case
+pub fn parse_weekday(day: &str) -> Option<Weekday> {
+    match day.to_lowercase().as_str() {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "tuesday" | "tue" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday" | "thu" => Some(Weekday::Thu),
+        "friday" | "fri" => Some(Weekday::Fri),
+        "saturday" | "sat" => Some(Weekday::Sat),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_weekdays(days: &[String]) -> Result<Vec<Weekday>, String> {
+    days.iter()
+        .map(|day| parse_weekday(day).ok_or_else(|| format!("Unknown day of the week '{}'", day)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_compute_end_date() {
+        let mut calendar = Calendar::new();
+        let monday = date(2023, 8, 21);
+
+        let (end, elapsed) = calendar.compute_end_date(monday, 5).unwrap();
+        assert_eq!(end, date(2023, 8, 25));
+        assert_eq!(elapsed.num_days(), 4);
+        assert_eq!(
+            calendar.compute_end_date(monday, 6).unwrap().0,
+            date(2023, 8, 28)
+        );
+        assert_eq!(calendar.compute_end_date(monday, 0).unwrap().0, monday);
+
+        // Starting on a weekend counts from the next working day
+        let saturday = date(2023, 8, 26);
+        assert_eq!(
+            calendar.compute_end_date(saturday, 1).unwrap().0,
+            date(2023, 8, 28)
+        );
+
+        calendar.add_holiday(date(2023, 8, 23));
+        assert_eq!(
+            calendar.compute_end_date(monday, 5).unwrap().0,
+            date(2023, 8, 28)
+        );
+        assert!(calendar.compute_end_date(monday, -1).is_err());
+
+        calendar.set_work_days(&[]);
+        assert!(calendar.compute_end_date(monday, 1).is_err());
+    }
+
+    #[test]
+    fn test_parse_calendar() {
+        let yaml = "work_days: [Mon, tuesday, WED]\nholidays:\n  - 2024-12-25\n";
+        let calendar = Calendar::from_str(yaml).unwrap();
+        assert!(calendar.is_work_day(Weekday::Tue));
+        assert!(!calendar.is_work_day(Weekday::Thu));
+        assert!(calendar.is_holiday(date(2024, 12, 25)));
+        assert!(!calendar.is_working_day(date(2024, 12, 25)));
+
+        let json = r#"{"holidays": ["2024-01-01"]}"#;
+        let calendar = Calendar::from_str(json).unwrap();
+        assert!(calendar.is_work_day(Weekday::Fri));
+        assert!(!calendar.is_work_day(Weekday::Sat));
+        assert!(calendar.is_holiday(date(2024, 1, 1)));
+
+        let error = Calendar::from_str("work_days: [Mon, Funday]").unwrap_err();
+        assert_eq!(error.to_string(), "Unknown day of the week 'Funday'");
+        assert!(Calendar::from_str("holidays: [2024-13-01]").is_err());
+        let error = Calendar::from_str("work_days: []").unwrap_err();
+        assert_eq!(error.to_string(), "No work days defined");
+    }
+
+    #[test]
+    fn test_yearly_holidays_and_ranges() {
+        let yaml = "
+holidays:
+  - 07-04
+  - { name: Thanksgiving, month: 11, weekday: Thu, nth: 4 }
+  - { month: 5, weekday: Mon, nth: -1 }
+  - { from: 12-24, to: 01-02 }
+  - { from: 2024-08-12, to: 2024-08-14 }
+  - { easter: 1 }
+half_days: [11-29]
+hours_per_day: 7.5
+";
+        let calendar = Calendar::from_str(yaml).unwrap();
+        assert!(calendar.is_holiday(date(2031, 7, 4)));
+        assert!(calendar.is_holiday(date(2024, 11, 28)));
+        assert!(!calendar.is_holiday(date(2024, 11, 21)));
+        assert!(calendar.is_holiday(date(2024, 5, 27)));
+        assert!(!calendar.is_holiday(date(2024, 5, 20)));
+        // The shutdown wraps round the new year
+        assert!(calendar.is_holiday(date(2024, 12, 30)));
+        assert!(calendar.is_holiday(date(2025, 1, 2)));
+        assert!(!calendar.is_holiday(date(2025, 1, 3)));
+        assert!(calendar.is_holiday(date(2024, 8, 13)));
+        assert!(!calendar.is_holiday(date(2025, 8, 13)));
+        // Easter Monday
+        assert!(calendar.is_holiday(date(2024, 4, 1)));
+        assert!(calendar.is_holiday(date(2025, 4, 21)));
+
+        assert_eq!(calendar.day_length(date(2024, 11, 29)), 0.5);
+        assert_eq!(calendar.day_length(date(2024, 11, 28)), 0.0);
+        assert_eq!(calendar.day_length(date(2024, 11, 27)), 1.0);
+        assert_eq!(calendar.hours_per_day(), 7.5);
+
+        for (yaml, error) in [
+            ("holidays: [13-01]", "Invalid date '13-01'"),
+            (
+                "holidays: [{ from: 12-24, to: 2025-01-02 }]",
+                "Range from 12-24 to 2025-01-02 must give both years or neither",
+            ),
+            (
+                "holidays: [{ from: 2025-01-02, to: 2024-12-24 }]",
+                "Range from 2025-01-02 to 2024-12-24 ends before it starts",
+            ),
+            (
+                "holidays: [{ month: 11, weekday: Thu, nth: 6 }]",
+                "Invalid month 11 or nth 6",
+            ),
+            (
+                "hours_per_day: 0",
+                "Hours per day must be above 0 and at most 24",
+            ),
+            (
+                "holiday_sets: [atlantis]",
+                "Unknown holiday set 'atlantis', expected one of: de, uk, us",
+            ),
+            (
+                "holidays: [{ from: 01-01, to: 12-31 }]",
+                "No working days left after holidays",
+            ),
+            (
+                "work_days: [Sat, Sun]\nholidays: [{ from: 06-01, to: 05-31 }]",
+                "No working days left after holidays",
+            ),
+        ] {
+            assert_eq!(Calendar::from_str(yaml).unwrap_err().to_string(), error);
+        }
+    }
+
+    #[test]
+    fn test_holiday_sets() {
+        let calendar = Calendar::from_str("holiday_sets: [US, uk]").unwrap();
+        // Thanksgiving, Memorial Day and Juneteenth
+        assert!(calendar.is_holiday(date(2026, 11, 26)));
+        assert!(calendar.is_holiday(date(2026, 5, 25)));
+        assert!(calendar.is_holiday(date(2026, 6, 19)));
+        // Good Friday and the summer bank holiday
+        assert!(calendar.is_holiday(date(2026, 4, 3)));
+        assert!(calendar.is_holiday(date(2026, 8, 31)));
+        assert!(!calendar.is_holiday(date(2026, 8, 24)));
+
+        let calendar = Calendar::from_str("holiday_sets: [de]").unwrap();
+        // Ascension Day, Whit Monday and German Unity Day
+        assert!(calendar.is_holiday(date(2026, 5, 14)));
+        assert!(calendar.is_holiday(date(2026, 5, 25)));
+        assert!(calendar.is_holiday(date(2026, 10, 3)));
+    }
+
+    #[test]
+    fn test_date_after() {
+        let mut calendar = Calendar::new();
+        let monday = date(2024, 8, 5);
+        calendar.add_half_day(date(2024, 8, 6));
+
+        let finish = |days| calendar.date_after(monday, days, true).unwrap();
+        assert_eq!(finish(0.0), monday);
+        assert_eq!(finish(1.0), monday);
+        assert_eq!(finish(1.5), date(2024, 8, 6));
+        assert_eq!(finish(1.75), date(2024, 8, 7));
+        assert_eq!(finish(4.5), date(2024, 8, 9));
+        assert_eq!(finish(5.0), date(2024, 8, 12));
+
+        let start = |days| calendar.date_after(monday, days, false).unwrap();
+        assert_eq!(start(0.0), monday);
+        assert_eq!(start(1.0), date(2024, 8, 6));
+        assert_eq!(start(1.5), date(2024, 8, 7));
+    }
+}
@@ -0,0 +1,223 @@
+// Confidence bands on simulated percentiles, and running simulations in
+// batches until the key percentiles are known precisely enough
+use crate::{
+    forecast::percentile,
+    schedule::Project,
+    simulation::{
+        run_detailed_range, run_multiple_range, Outcomes, SimulationOptions, SimulationResult,
+    },
+};
+
+use std::{ops::Range, time::Duration};
+
+// Normal quantile for a two-sided 95% interval
+const Z_95: f64 = 1.959963984540054;
+
+// Completion time percentiles that must be stable before the simulation stops
+pub const KEY_PERCENTILES: [f64; 3] = [0.5, 0.85, 0.95];
+
+// A simulated percentile with a 95% confidence interval
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PercentileBand {
+    pub percentile: f64,
+    pub value: Duration,
+    pub lower: Duration,
+    pub upper: Duration,
+}
+
+impl PercentileBand {
+    // The interval comes from the order statistics either side of the
+    // percentile, so it holds whatever the shape of the distribution
+    pub fn new(sorted: &[Duration], p: f64) -> Self {
+        let n = sorted.len() as f64;
+        let spread = Z_95 * (n * p * (1.0 - p)).sqrt();
+        let last = sorted.len() - 1;
+        let lower = ((n * p - spread).floor().max(0.0) as usize).min(last);
+        let upper = ((n * p + spread).ceil() as usize).min(last);
+        PercentileBand {
+            percentile: p,
+            value: percentile(sorted, p),
+            lower: sorted[lower],
+            upper: sorted[upper],
+        }
+    }
+
+    pub fn half_width(&self) -> Duration {
+        (self.upper - self.lower) / 2
+    }
+
+    pub fn standard_error(&self) -> Duration {
+        (self.upper - self.lower).div_f64(2.0 * Z_95)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConvergenceOptions {
+    // Largest acceptable half-width of each key percentile's 95% band
+    pub precision: Duration,
+    pub batch_size: usize,
+    pub max_iterations: usize,
+}
+
+impl ConvergenceOptions {
+    pub fn new(precision: Duration) -> Self {
+        ConvergenceOptions {
+            precision,
+            batch_size: 10_000,
+            max_iterations: 10_000_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Convergence {
+    pub iterations: usize,
+    // False when `max_iterations` ran out or the run was cancelled first
+    pub converged: bool,
+    // One per key percentile of the completion time, or none if cancelled
+    // before any iterations finished
+    pub bands: Vec<PercentileBand>,
+}
+
+fn converge<T>(
+    run_batch: impl Fn(Range<usize>) -> Result<Vec<T>, String>,
+    duration: impl Fn(&T) -> Duration,
+    simulation: &SimulationOptions,
+    options: &ConvergenceOptions,
+) -> Result<(Vec<T>, Convergence), String> {
+    let mut results = Vec::new();
+    loop {
+        let start = results.len();
+        let end = (start + options.batch_size.max(1)).min(options.max_iterations.max(1));
+        results.extend(run_batch(start..end)?);
+
+        let cancelled = simulation.is_cancelled();
+        if cancelled && results.is_empty() {
+            let convergence = Convergence {
+                iterations: 0,
+                converged: false,
+                bands: vec![],
+            };
+            return Ok((results, convergence));
+        }
+
+        let mut sorted: Vec<_> = results.iter().map(&duration).collect();
+        sorted.sort_unstable();
+        let bands: Vec<_> = KEY_PERCENTILES
+            .iter()
+            .map(|&p| PercentileBand::new(&sorted, p))
+            .collect();
+        let converged = bands
+            .iter()
+            .all(|band| band.half_width() <= options.precision);
+        if converged || cancelled || results.len() >= options.max_iterations {
+            let convergence = Convergence {
+                iterations: results.len(),
+                converged,
+                bands,
+            };
+            return Ok((results, convergence));
+        }
+    }
+}
+
+// Runs batches of simulations until the 95% confidence band of the p50, p85
+// and p95 completion times is within ± `precision`, returning the completion
+// and effort times like `run_multiple_simulations`
+pub fn run_multiple_until_converged(
+    schedule: &Project,
+    options: &SimulationOptions,
+    convergence: &ConvergenceOptions,
+) -> Result<(Outcomes, Convergence), String> {
+    let (results, convergence) = converge(
+        |iterations| {
+            let (durations, efforts) = run_multiple_range(schedule, options, iterations)?;
+            Ok(durations.into_iter().zip(efforts).collect())
+        },
+        |&(duration, _)| duration,
+        options,
+        convergence,
+    )?;
+    Ok((results.into_iter().unzip(), convergence))
+}
+
+// As `run_multiple_until_converged`, keeping every simulation's task times
+pub fn run_detailed_until_converged(
+    schedule: &Project,
+    options: &SimulationOptions,
+    convergence: &ConvergenceOptions,
+) -> Result<(Vec<SimulationResult>, Convergence), String> {
+    converge(
+        |iterations| run_detailed_range(schedule, options, iterations),
+        |result| result.total_project_duration,
+        options,
+        convergence,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        simulation::run_multiple_simulations_with_options,
+        task::{days_to_duration, Task},
+    };
+
+    #[test]
+    fn test_percentile_band() {
+        let sorted: Vec<_> = (0..1000).map(|i| days_to_duration(i as f64)).collect();
+        let band = PercentileBand::new(&sorted, 0.5);
+
+        assert_eq!(band.value, days_to_duration(500.0));
+        // 1.96 * sqrt(250) is 31 either side
+        assert_eq!(band.lower, days_to_duration(469.0));
+        assert_eq!(band.upper, days_to_duration(531.0));
+        assert_eq!(band.half_width(), days_to_duration(31.0));
+
+        // Bands at the extremes stay within the data
+        let band = PercentileBand::new(&sorted[..10], 0.95);
+        assert_eq!(band.upper, days_to_duration(9.0));
+    }
+
+    #[test]
+    fn test_run_until_converged() {
+        let task = Task::new(
+            "A",
+            vec![],
+            days_to_duration(1.0),
+            days_to_duration(2.0),
+            days_to_duration(4.0),
+        );
+        let project = Project::new(vec![task], 1, None).unwrap();
+        let options = SimulationOptions {
+            seed: Some(3),
+            ..Default::default()
+        };
+
+        let mut convergence = ConvergenceOptions::new(days_to_duration(0.05));
+        convergence.batch_size = 500;
+        let ((durations, efforts), result) =
+            run_multiple_until_converged(&project, &options, &convergence).unwrap();
+
+        assert!(result.converged);
+        assert_eq!(result.iterations % 500, 0);
+        assert!(result.iterations > 500);
+        assert_eq!(durations.len(), result.iterations);
+        assert!(result
+            .bands
+            .iter()
+            .all(|band| band.half_width() <= days_to_duration(0.05)));
+        // Batches continue the seeded sequence of a single run
+        let single =
+            run_multiple_simulations_with_options(&project, result.iterations, &options).unwrap();
+        assert_eq!((durations, efforts), single);
+
+        // Gives up at the limit
+        convergence.precision = Duration::ZERO;
+        convergence.max_iterations = 1200;
+        let (results, result) =
+            run_detailed_until_converged(&project, &options, &convergence).unwrap();
+        assert!(!result.converged);
+        assert_eq!(results.len(), 1200);
+    }
+}
@@ -0,0 +1,401 @@
+use crate::{
+    calendar::Calendar, schedule::Project, simulation::SimulationResult, timeline::Timeline,
+};
+
+use chrono::NaiveDate;
+
+use std::{collections::HashMap, time::Duration};
+
+// Value at percentile `p` (0.0..=1.0) of already sorted data
+pub fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    let index = (p * (sorted.len() - 1) as f64).round() as usize;
+    sorted[index]
+}
+
+#[derive(Debug)]
+pub struct TaskForecast {
+    // Sorted across all simulations
+    pub start_times: Vec<Duration>,
+    pub finish_times: Vec<Duration>,
+}
+
+impl TaskForecast {
+    pub fn start(&self, p: f64) -> Duration {
+        percentile(&self.start_times, p)
+    }
+
+    pub fn finish(&self, p: f64) -> Duration {
+        percentile(&self.finish_times, p)
+    }
+}
+
+#[derive(Debug)]
+pub struct MilestoneForecast {
+    // Sorted across all simulations
+    pub finish_times: Vec<Duration>,
+}
+
+impl MilestoneForecast {
+    pub fn finish(&self, p: f64) -> Duration {
+        percentile(&self.finish_times, p)
+    }
+}
+
+#[derive(Debug)]
+pub struct ScheduleForecast {
+    // Indexed like `Project::tasks` and `Project::milestones`
+    pub tasks: Vec<TaskForecast>,
+    pub milestones: Vec<MilestoneForecast>,
+}
+
+impl ScheduleForecast {
+    pub fn new(project: &Project, results: &[SimulationResult]) -> Self {
+        ScheduleForecast {
+            tasks: forecast_tasks(project, results),
+            milestones: forecast_milestones(project, results),
+        }
+    }
+}
+
+// One forecast per task, indexed like `Project::tasks`
+pub fn forecast_tasks(project: &Project, results: &[SimulationResult]) -> Vec<TaskForecast> {
+    (0..project.tasks.len())
+        .map(|i| {
+            let mut start_times: Vec<_> = results.iter().map(|r| r.task_start_times[i]).collect();
+            let mut finish_times: Vec<_> = results.iter().map(|r| r.task_finish_times[i]).collect();
+            start_times.sort_unstable();
+            finish_times.sort_unstable();
+            TaskForecast {
+                start_times,
+                finish_times,
+            }
+        })
+        .collect()
+}
+
+// One forecast per milestone, each reached when its last dependency finishes
+pub fn forecast_milestones(
+    project: &Project,
+    results: &[SimulationResult],
+) -> Vec<MilestoneForecast> {
+    let index: HashMap<&str, usize> = project
+        .tasks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (t.id.as_str(), i))
+        .collect();
+    project
+        .milestones
+        .iter()
+        .map(|milestone| {
+            let mut finish_times: Vec<_> = results
+                .iter()
+                .map(|r| {
+                    milestone
+                        .dependencies
+                        .iter()
+                        .map(|dep| r.task_finish_times[index[dep.as_str()]])
+                        .max()
+                        .unwrap_or_default()
+                })
+                .collect();
+            finish_times.sort_unstable();
+            MilestoneForecast { finish_times }
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DeadlineForecast {
+    pub name: String,
+    pub deadline: NaiveDate,
+    // Fraction of simulations in which the task finishes after the deadline
+    pub miss_chance: f64,
+}
+
+// One forecast per task with a `must_finish_by` date, in project order. A task
+// is late when it finishes after the end of that day on the project calendar.
+pub fn forecast_deadlines(
+    project: &Project,
+    results: &[SimulationResult],
+) -> Vec<DeadlineForecast> {
+    let timeline = Timeline::new(project);
+    project
+        .tasks
+        .iter()
+        .enumerate()
+        .filter_map(|(i, task)| {
+            let deadline = task.must_finish_by?;
+            let due = timeline.workdays_by(deadline);
+            let late = results
+                .iter()
+                .filter(|r| r.task_finish_times[i] > due)
+                .count();
+            Some(DeadlineForecast {
+                name: task.display_name().to_string(),
+                deadline,
+                miss_chance: late as f64 / results.len() as f64,
+            })
+        })
+        .collect()
+}
+
+// Fraction of simulations in which each task is on the critical path, i.e. the
+// chain of tasks ending with the last to finish in which each task started the
+// moment the one before it finished. The task before may be a dependency, or
+// another task whose worker the task was waiting for.
+pub fn criticality(project: &Project, results: &[SimulationResult]) -> Vec<f64> {
+    let index: HashMap<&str, usize> = project
+        .tasks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (t.id.as_str(), i))
+        .collect();
+    let mut counts = vec![0usize; project.tasks.len()];
+    for result in results {
+        let mut critical = vec![false; project.tasks.len()];
+        let mut pending: Vec<usize> = (0..project.tasks.len())
+            .filter(|&i| result.task_finish_times[i] == result.total_project_duration)
+            .collect();
+        while let Some(i) = pending.pop() {
+            if std::mem::replace(&mut critical[i], true) {
+                continue;
+            }
+            let start = result.task_start_times[i];
+            if start.is_zero() {
+                continue;
+            }
+            let before: Vec<usize> = project.tasks[i]
+                .dependencies
+                .iter()
+                .map(|dep| index[dep.as_str()])
+                .filter(|&d| result.task_finish_times[d] == start)
+                .collect();
+            if before.is_empty() {
+                pending.extend(
+                    (0..project.tasks.len()).filter(|&d| result.task_finish_times[d] == start),
+                );
+            } else {
+                pending.extend(before);
+            }
+        }
+        for (count, on_path) in counts.iter_mut().zip(critical) {
+            *count += on_path as usize;
+        }
+    }
+    counts
+        .into_iter()
+        .map(|count| count as f64 / results.len() as f64)
+        .collect()
+}
+
+// Work day on which work resumes after `elapsed` workdays have passed
+pub fn start_date_after(
+    calendar: &Calendar,
+    start: NaiveDate,
+    elapsed: Duration,
+) -> Result<NaiveDate, String> {
+    calendar.date_after(start, elapsed.as_secs_f64() / 86400.0, false)
+}
+
+// Work day on which `elapsed` workdays of work are complete
+pub fn finish_date_after(
+    calendar: &Calendar,
+    start: NaiveDate,
+    elapsed: Duration,
+) -> Result<NaiveDate, String> {
+    calendar.date_after(start, elapsed.as_secs_f64() / 86400.0, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        schedule::Milestone,
+        task::{days_to_duration, Task},
+    };
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_percentile() {
+        let sorted: Vec<_> = (0..=100).map(Duration::from_secs).collect();
+        assert_eq!(percentile(&sorted, 0.0), Duration::from_secs(0));
+        assert_eq!(percentile(&sorted, 0.5), Duration::from_secs(50));
+        assert_eq!(percentile(&sorted, 0.85), Duration::from_secs(85));
+        assert_eq!(percentile(&sorted, 1.0), Duration::from_secs(100));
+    }
+
+    #[test]
+    fn test_forecast_tasks() {
+        let task = Task::new(
+            "A",
+            vec![],
+            Duration::from_secs(1),
+            Duration::from_secs(2),
+            Duration::from_secs(3),
+        );
+        let project = Project::new(vec![task], 1, None).unwrap();
+        let results: Vec<_> = [3, 1, 2]
+            .iter()
+            .map(|&secs| SimulationResult {
+                total_project_duration: Duration::from_secs(secs),
+                total_effort_time: Duration::from_secs(secs),
+                task_start_times: vec![Duration::from_secs(secs - 1)],
+                task_finish_times: vec![Duration::from_secs(secs)],
+                task_effort_times: vec![Duration::from_secs(1)],
+            })
+            .collect();
+
+        let forecasts = forecast_tasks(&project, &results);
+        assert_eq!(forecasts.len(), 1);
+        assert_eq!(forecasts[0].start(0.0), Duration::from_secs(0));
+        assert_eq!(forecasts[0].finish(0.5), Duration::from_secs(2));
+        assert_eq!(forecasts[0].finish(1.0), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_forecast_milestones() {
+        let task = |id: &str| {
+            Task::new(
+                id,
+                vec![],
+                Duration::from_secs(1),
+                Duration::from_secs(2),
+                Duration::from_secs(3),
+            )
+        };
+        let mut project = Project::new(vec![task("A"), task("B")], 2, None).unwrap();
+        project.milestones = vec![
+            Milestone {
+                id: "Beta".to_string(),
+                dependencies: vec!["A".to_string(), "B".to_string()],
+                ..Default::default()
+            },
+            Milestone {
+                id: "Kickoff".to_string(),
+                ..Default::default()
+            },
+        ];
+        let results: Vec<_> = [(3, 1), (1, 2)]
+            .iter()
+            .map(|&(a, b)| SimulationResult {
+                total_project_duration: Duration::from_secs(a.max(b)),
+                total_effort_time: Duration::from_secs(a + b),
+                task_start_times: vec![Duration::ZERO; 2],
+                task_finish_times: vec![Duration::from_secs(a), Duration::from_secs(b)],
+                task_effort_times: vec![Duration::from_secs(a), Duration::from_secs(b)],
+            })
+            .collect();
+
+        let forecasts = forecast_milestones(&project, &results);
+        assert_eq!(
+            forecasts[0].finish_times,
+            vec![Duration::from_secs(2), Duration::from_secs(3)]
+        );
+        assert_eq!(forecasts[1].finish(1.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_criticality() {
+        let task = |id: &str, deps: Vec<String>| {
+            Task::new(
+                id,
+                deps,
+                Duration::from_secs(1),
+                Duration::from_secs(2),
+                Duration::from_secs(3),
+            )
+        };
+        // B and C both follow A, D waits for C's worker
+        let project = Project::new(
+            vec![
+                task("A", vec![]),
+                task("B", vec!["A".to_string()]),
+                task("C", vec!["A".to_string()]),
+                task("D", vec![]),
+            ],
+            2,
+            None,
+        )
+        .unwrap();
+        let secs = |values: [u64; 4]| values.map(Duration::from_secs).to_vec();
+        let results = vec![
+            SimulationResult {
+                total_project_duration: Duration::from_secs(6),
+                total_effort_time: Duration::from_secs(10),
+                task_start_times: secs([0, 2, 2, 4]),
+                task_finish_times: secs([2, 5, 4, 6]),
+                task_effort_times: secs([2, 3, 2, 2]),
+            },
+            SimulationResult {
+                total_project_duration: Duration::from_secs(5),
+                total_effort_time: Duration::from_secs(8),
+                task_start_times: secs([0, 2, 2, 0]),
+                task_finish_times: secs([2, 5, 3, 1]),
+                task_effort_times: secs([2, 3, 1, 1]),
+            },
+        ];
+
+        assert_eq!(criticality(&project, &results), vec![1.0, 0.5, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_forecast_deadlines() {
+        let mut tasks: Vec<_> = ["A", "B"]
+            .iter()
+            .map(|id| {
+                Task::new(
+                    id,
+                    vec![],
+                    days_to_duration(1.0),
+                    days_to_duration(2.0),
+                    days_to_duration(3.0),
+                )
+            })
+            .collect();
+        tasks[0].name = Some("Alpha".to_string());
+        // The end of the second working day, past the weekend
+        tasks[0].must_finish_by = Some(date(2024, 8, 5));
+        let mut project = Project::new(tasks, 1, None).unwrap();
+        project.start_date = Some(date(2024, 8, 2));
+        let result = |finish: f64| SimulationResult {
+            total_project_duration: days_to_duration(finish),
+            total_effort_time: days_to_duration(finish),
+            task_start_times: vec![Duration::ZERO; 2],
+            task_finish_times: vec![days_to_duration(finish), Duration::ZERO],
+            task_effort_times: vec![days_to_duration(finish), Duration::ZERO],
+        };
+        let results: Vec<_> = [1.5, 2.0, 2.5, 3.0].into_iter().map(result).collect();
+
+        assert_eq!(
+            forecast_deadlines(&project, &results),
+            vec![DeadlineForecast {
+                name: "Alpha".to_string(),
+                deadline: date(2024, 8, 5),
+                miss_chance: 0.5,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_work_dates() {
+        let calendar = Calendar::new();
+        let monday = date(2024, 8, 5);
+
+        let start = |days| start_date_after(&calendar, monday, days_to_duration(days)).unwrap();
+        assert_eq!(start(0.0), monday);
+        assert_eq!(start(0.5), monday);
+        assert_eq!(start(1.0), date(2024, 8, 6));
+        assert_eq!(start(5.0), date(2024, 8, 12));
+
+        let finish = |days| finish_date_after(&calendar, monday, days_to_duration(days)).unwrap();
+        assert_eq!(finish(0.5), monday);
+        assert_eq!(finish(1.0), monday);
+        assert_eq!(finish(1.5), date(2024, 8, 6));
+        assert_eq!(finish(5.0), date(2024, 8, 9));
+        assert_eq!(finish(6.0), date(2024, 8, 12));
+    }
+}
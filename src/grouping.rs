@@ -0,0 +1,304 @@
+use crate::{schedule::Project, simulation::SimulationResult};
+
+use std::{collections::BTreeMap, str::FromStr, time::Duration};
+
+pub const UNTAGGED_GROUP: &str = "(untagged)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Tag,
+    // Every WBS node, or only those at the given depth (1 = top level)
+    Wbs(Option<usize>),
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "tag" | "tags" => Ok(GroupBy::Tag),
+            "wbs" => Ok(GroupBy::Wbs(None)),
+            level => match level.strip_prefix("wbs:").map(str::parse::<usize>) {
+                Some(Ok(depth)) if depth > 0 => Ok(GroupBy::Wbs(Some(depth))),
+                _ => Err(format!(
+                    "Unsupported grouping '{}'. Use 'tag', 'wbs' or 'wbs:<level>'",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskGroup {
+    pub name: String,
+    // Indices into `Project::tasks`
+    pub tasks: Vec<usize>,
+}
+
+#[derive(Debug)]
+pub struct GroupForecast {
+    pub name: String,
+    // One entry per simulation, each the sum of the group's task efforts
+    pub effort_times: Vec<Duration>,
+    // One entry per simulation, each the finish time of the group's last task
+    pub finish_times: Vec<Duration>,
+}
+
+impl Project {
+    pub fn group_tasks(&self, group_by: GroupBy) -> Vec<TaskGroup> {
+        match group_by {
+            GroupBy::Tag => {
+                let mut groups: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+                let mut untagged = Vec::new();
+                for (index, task) in self.tasks.iter().enumerate() {
+                    if task.tags.is_empty() {
+                        untagged.push(index);
+                    }
+                    for tag in &task.tags {
+                        let members = groups.entry(tag).or_default();
+                        if !members.contains(&index) {
+                            members.push(index);
+                        }
+                    }
+                }
+
+                let mut groups: Vec<_> = groups
+                    .into_iter()
+                    .map(|(name, tasks)| TaskGroup {
+                        name: name.to_string(),
+                        tasks,
+                    })
+                    .collect();
+                if !untagged.is_empty() {
+                    groups.push(TaskGroup {
+                        name: UNTAGGED_GROUP.to_string(),
+                        tasks: untagged,
+                    });
+                }
+                groups
+            }
+            GroupBy::Wbs(depth) => self
+                .wbs
+                .iter()
+                .filter(|node| {
+                    depth.map_or(true, |depth| {
+                        self.wbs_ancestors(Some(&node.id)).len() == depth
+                    })
+                })
+                .map(|node| TaskGroup {
+                    name: node.display_name().to_string(),
+                    tasks: self
+                        .tasks
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, task)| {
+                            self.wbs_ancestors(task.parent.as_deref())
+                                .contains(&node.id.as_str())
+                        })
+                        .map(|(index, _)| index)
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+pub fn forecast_groups(groups: &[TaskGroup], results: &[SimulationResult]) -> Vec<GroupForecast> {
+    groups
+        .iter()
+        .map(|group| {
+            let (effort_times, finish_times) = results
+                .iter()
+                .map(|result| {
+                    let effort: Duration = group
+                        .tasks
+                        .iter()
+                        .map(|&i| result.task_effort_times[i])
+                        .sum();
+                    let finish = group
+                        .tasks
+                        .iter()
+                        .map(|&i| result.task_finish_times[i])
+                        .max()
+                        .unwrap_or_default();
+                    (effort, finish)
+                })
+                .unzip();
+            GroupForecast {
+                name: group.name.clone(),
+                effort_times,
+                finish_times,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        calendar::Calendar,
+        schedule::{Focus, WbsNode},
+        task::Task,
+    };
+
+    fn create_task(id: &str, tags: &[&str]) -> Task {
+        let mut task = Task::new(
+            id,
+            vec![],
+            Duration::from_secs(1),
+            Duration::from_secs(2),
+            Duration::from_secs(3),
+        );
+        task.tags = tags.iter().map(|t| t.to_string()).collect();
+        task
+    }
+
+    fn create_result(finish: &[u64], effort: &[u64]) -> SimulationResult {
+        SimulationResult {
+            total_project_duration: Duration::from_secs(*finish.iter().max().unwrap()),
+            total_effort_time: Duration::from_secs(effort.iter().sum()),
+            task_start_times: vec![Duration::default(); finish.len()],
+            task_finish_times: finish.iter().map(|&s| Duration::from_secs(s)).collect(),
+            task_effort_times: effort.iter().map(|&s| Duration::from_secs(s)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_group_by() {
+        assert_eq!("tag".parse::<GroupBy>(), Ok(GroupBy::Tag));
+        assert_eq!("Tags".parse::<GroupBy>(), Ok(GroupBy::Tag));
+        assert_eq!("wbs".parse::<GroupBy>(), Ok(GroupBy::Wbs(None)));
+        assert_eq!("WBS:2".parse::<GroupBy>(), Ok(GroupBy::Wbs(Some(2))));
+        assert!("wbs:0".parse::<GroupBy>().is_err());
+        assert!("wbs:x".parse::<GroupBy>().is_err());
+        assert!("owner".parse::<GroupBy>().is_err());
+    }
+
+    #[test]
+    fn test_group_by_wbs() {
+        let mut tasks = vec![
+            create_task("Cart", &[]),
+            create_task("Payment", &[]),
+            create_task("Receipt", &[]),
+            create_task("Launch", &[]),
+        ];
+        tasks[0].parent = Some("Story".to_string());
+        tasks[1].parent = Some("Story".to_string());
+        tasks[2].parent = Some("Epic".to_string());
+        let project = Project {
+            tasks,
+            num_workers: 1,
+            start_date: None,
+            workers: vec![],
+            calendar: Calendar::new(),
+            focus: Focus::FULL,
+            interruptions: None,
+            wbs: vec![
+                WbsNode {
+                    id: "Epic".to_string(),
+                    name: Some("Checkout".to_string()),
+                    parent: None,
+                },
+                WbsNode {
+                    id: "Story".to_string(),
+                    name: None,
+                    parent: Some("Epic".to_string()),
+                },
+            ],
+            milestones: Vec::new(),
+        };
+
+        assert_eq!(
+            project.group_tasks(GroupBy::Wbs(None)),
+            vec![
+                TaskGroup {
+                    name: "Checkout".to_string(),
+                    tasks: vec![0, 1, 2],
+                },
+                TaskGroup {
+                    name: "Story".to_string(),
+                    tasks: vec![0, 1],
+                },
+            ]
+        );
+        assert_eq!(
+            project.group_tasks(GroupBy::Wbs(Some(2))),
+            vec![TaskGroup {
+                name: "Story".to_string(),
+                tasks: vec![0, 1],
+            }]
+        );
+        assert!(project.group_tasks(GroupBy::Wbs(Some(3))).is_empty());
+    }
+
+    #[test]
+    fn test_group_by_tag() {
+        let tasks = vec![
+            create_task("A", &["frontend"]),
+            create_task("B", &["infra", "frontend"]),
+            create_task("C", &[]),
+            create_task("D", &["infra", "infra"]),
+        ];
+        let project = Project::new(tasks, 1, None).unwrap();
+
+        let groups = project.group_tasks(GroupBy::Tag);
+        assert_eq!(
+            groups,
+            vec![
+                TaskGroup {
+                    name: "frontend".to_string(),
+                    tasks: vec![0, 1],
+                },
+                TaskGroup {
+                    name: "infra".to_string(),
+                    tasks: vec![1, 3],
+                },
+                TaskGroup {
+                    name: UNTAGGED_GROUP.to_string(),
+                    tasks: vec![2],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_forecast_groups() {
+        let groups = vec![
+            TaskGroup {
+                name: "frontend".to_string(),
+                tasks: vec![0, 1],
+            },
+            TaskGroup {
+                name: "infra".to_string(),
+                tasks: vec![2],
+            },
+        ];
+        let results = vec![
+            create_result(&[2, 5, 4], &[2, 3, 4]),
+            create_result(&[3, 4, 7], &[3, 1, 4]),
+        ];
+
+        let forecasts = forecast_groups(&groups, &results);
+
+        assert_eq!(forecasts[0].name, "frontend");
+        assert_eq!(
+            forecasts[0].effort_times,
+            vec![Duration::from_secs(5), Duration::from_secs(4)]
+        );
+        assert_eq!(
+            forecasts[0].finish_times,
+            vec![Duration::from_secs(5), Duration::from_secs(4)]
+        );
+        assert_eq!(forecasts[1].name, "infra");
+        assert_eq!(
+            forecasts[1].effort_times,
+            vec![Duration::from_secs(4), Duration::from_secs(4)]
+        );
+        assert_eq!(
+            forecasts[1].finish_times,
+            vec![Duration::from_secs(4), Duration::from_secs(7)]
+        );
+    }
+}
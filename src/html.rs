@@ -0,0 +1,306 @@
+// Self-contained HTML report, with charts as inline SVG
+use crate::{
+    calendar::Calendar,
+    forecast::{criticality, finish_date_after, percentile, ScheduleForecast},
+    schedule::Project,
+    simulation::SimulationResult,
+    stats::{likely_critical_path, Summary},
+    svg,
+    units::DisplayUnit,
+};
+
+use chrono::NaiveDate;
+use quick_xml::escape::escape;
+
+use std::{fmt::Write, time::Duration};
+
+const HISTOGRAM_BINS: usize = 40;
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em auto; max-width: 1320px; color: #222; }
+h1 { margin-bottom: 0.2em; }
+.charts { display: flex; flex-wrap: wrap; gap: 16px; }
+.scroll { overflow-x: auto; border: 1px solid #ddd; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { padding: 4px 10px; border-bottom: 1px solid #ddd; text-align: left; }
+td.number { text-align: right; font-variant-numeric: tabular-nums; }
+.bar { background: #d62728; height: 10px; }
+";
+
+struct Report<'a> {
+    html: String,
+    calendar: &'a Calendar,
+    start: NaiveDate,
+    unit: DisplayUnit,
+}
+
+impl Report<'_> {
+    fn date(&self, elapsed: Duration) -> Result<NaiveDate, String> {
+        finish_date_after(self.calendar, self.start, elapsed)
+    }
+
+    // Charts, a percentile table and summary statistics for one outcome,
+    // compared with the single-point `plan` if there is one
+    fn distribution(
+        &mut self,
+        title: &str,
+        data: &[Duration],
+        plan: Option<Duration>,
+    ) -> Result<(), String> {
+        let unit = self.unit;
+        let mut sorted = data.to_vec();
+        sorted.sort_unstable();
+        writeln!(self.html, "<h2>{}</h2>", escape(title)).unwrap();
+        writeln!(self.html, "<div class=\"charts\">").unwrap();
+        self.html
+            .push_str(&svg::cdf(&sorted, &format!("{} (CDF)", title), unit));
+        self.html.push_str(&svg::histogram(
+            &sorted,
+            HISTOGRAM_BINS,
+            &format!("{} (histogram)", title),
+            unit,
+        ));
+        writeln!(self.html, "</div>").unwrap();
+        writeln!(
+            self.html,
+            "<table><tr><th>Percentile</th><th>{}</th><th>Calendar days</th><th>Date</th></tr>",
+            unit.heading()
+        )
+        .unwrap();
+        for step in (5..=95).step_by(5).chain([99]) {
+            let value = percentile(&sorted, step as f64 / 100.0);
+            let date = self.date(value)?;
+            writeln!(
+                self.html,
+                "<tr><td>p{}</td><td class=\"number\">{:.1}</td><td class=\"number\">{}</td><td>{}</td></tr>",
+                step,
+                unit.value(value),
+                (date - self.start).num_days() + 1,
+                date
+            )
+            .unwrap();
+        }
+        writeln!(self.html, "</table>").unwrap();
+
+        let stats = Summary::new(data);
+        writeln!(self.html, "<table>").unwrap();
+        let mut row = |name: &str, value: String| {
+            writeln!(
+                self.html,
+                "<tr><th>{}</th><td class=\"number\">{}</td></tr>",
+                name, value
+            )
+            .unwrap();
+        };
+        let amount = |duration| format!("{:.1} {}", unit.value(duration), unit.name());
+        row("Mean", amount(stats.mean));
+        row("Standard deviation", amount(stats.std_dev));
+        row(
+            "Coefficient of variation",
+            format!("{:.1}%", stats.coefficient_of_variation() * 100.0),
+        );
+        row("Skewness", format!("{:.2}", stats.skewness));
+        row("Minimum", amount(stats.min));
+        row("Maximum", amount(stats.max));
+        if let Some(plan) = plan {
+            let longer =
+                |p: f64| (percentile(&sorted, p).as_secs_f64() / plan.as_secs_f64() - 1.0) * 100.0;
+            row(
+                "Sum of likely estimates along the critical path",
+                format!(
+                    "{} (p50 {:+.0}%, p85 {:+.0}%)",
+                    amount(plan),
+                    longer(0.5),
+                    longer(0.85)
+                ),
+            );
+        }
+        writeln!(self.html, "</table>").unwrap();
+        Ok(())
+    }
+}
+
+// Writes a single HTML page with completion and effort charts, per-task and
+// milestone finish dates, a criticality ranking and the dependency graph, with
+// times shown in `unit`
+pub fn report(
+    project: &Project,
+    results: &[SimulationResult],
+    calendar: &Calendar,
+    start: NaiveDate,
+    unit: DisplayUnit,
+) -> Result<String, String> {
+    let forecast = ScheduleForecast::new(project, results);
+    let criticality = criticality(project, results);
+    let mut report = Report {
+        html: String::new(),
+        calendar,
+        start,
+        unit,
+    };
+
+    writeln!(
+        report.html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Project forecast</title>\n<style>\n{}</style>\n</head>\n<body>",
+        STYLE
+    )
+    .unwrap();
+    writeln!(report.html, "<h1>Project forecast</h1>").unwrap();
+    writeln!(
+        report.html,
+        "<p>{} simulations of {} tasks with {} worker{}, starting {}.</p>",
+        results.len(),
+        project.tasks.len(),
+        project.num_workers,
+        if project.num_workers == 1 { "" } else { "s" },
+        start
+    )
+    .unwrap();
+
+    let durations: Vec<_> = results.iter().map(|r| r.total_project_duration).collect();
+    let efforts: Vec<_> = results.iter().map(|r| r.total_effort_time).collect();
+    let plan = likely_critical_path(project);
+    report.distribution("Completion time", &durations, Some(plan.duration))?;
+    let path: Vec<_> = plan
+        .tasks
+        .iter()
+        .map(|&i| escape(project.tasks[i].display_name()))
+        .collect();
+    writeln!(
+        report.html,
+        "<p>Critical path with every task taking its likely estimate: {}.</p>",
+        path.join(" &rarr; ")
+    )
+    .unwrap();
+    report.distribution("Total work effort (1 worker)", &efforts, None)?;
+
+    if !project.milestones.is_empty() {
+        writeln!(
+            report.html,
+            "<h2>Milestones</h2>\n<table><tr><th>Milestone</th><th>p50</th><th>p85</th><th>p95</th></tr>"
+        )
+        .unwrap();
+        for (milestone, forecast) in project.milestones.iter().zip(&forecast.milestones) {
+            writeln!(
+                report.html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(milestone.display_name()),
+                report.date(forecast.finish(0.5))?,
+                report.date(forecast.finish(0.85))?,
+                report.date(forecast.finish(0.95))?
+            )
+            .unwrap();
+        }
+        writeln!(report.html, "</table>").unwrap();
+    }
+
+    writeln!(
+        report.html,
+        "<h2>Task finish dates</h2>\n<table><tr><th>Task</th><th>Owner</th><th>p50</th><th>p85</th><th>p95</th></tr>"
+    )
+    .unwrap();
+    for (task, forecast) in project.tasks.iter().zip(&forecast.tasks) {
+        writeln!(
+            report.html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(task.display_name()),
+            escape(task.owner.as_deref().unwrap_or("")),
+            report.date(forecast.finish(0.5))?,
+            report.date(forecast.finish(0.85))?,
+            report.date(forecast.finish(0.95))?
+        )
+        .unwrap();
+    }
+    writeln!(report.html, "</table>").unwrap();
+
+    // Most critical first, in project order when tied
+    let mut ranking: Vec<usize> = (0..project.tasks.len()).collect();
+    ranking.sort_by(|&a, &b| criticality[b].total_cmp(&criticality[a]));
+    writeln!(
+        report.html,
+        "<h2>Criticality</h2>\n<p>How often each task was on the critical path: the chain of tasks, each starting as the one before it finished, that determined the completion time.</p>\n<table><tr><th>Task</th><th>Critical</th><th></th></tr>"
+    )
+    .unwrap();
+    for i in ranking {
+        writeln!(
+            report.html,
+            "<tr><td>{}</td><td class=\"number\">{:.1}%</td><td><div class=\"bar\" style=\"width: {:.0}px\"></div></td></tr>",
+            escape(project.tasks[i].display_name()),
+            criticality[i] * 100.0,
+            criticality[i] * 200.0
+        )
+        .unwrap();
+    }
+    writeln!(report.html, "</table>").unwrap();
+
+    writeln!(
+        report.html,
+        "<h2>Dependencies</h2>\n<p>Tasks are shaded by criticality and milestones are shown as diamonds.</p>\n<div class=\"scroll\">"
+    )
+    .unwrap();
+    report
+        .html
+        .push_str(&svg::dependency_graph(project, &criticality));
+    writeln!(report.html, "</div>\n</body>\n</html>").unwrap();
+
+    Ok(report.html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        schedule::Milestone,
+        simulation::run_detailed_simulations,
+        task::{days_to_duration, Task},
+        units::Unit,
+    };
+
+    #[test]
+    fn test_report() {
+        let mut design = Task::new(
+            "Design",
+            vec![],
+            days_to_duration(1.0),
+            days_to_duration(2.0),
+            days_to_duration(3.0),
+        );
+        design.name = Some("Design <API>".to_string());
+        let build = Task::new(
+            "Build",
+            vec!["Design".to_string()],
+            days_to_duration(2.0),
+            days_to_duration(3.0),
+            days_to_duration(5.0),
+        );
+        let mut project = Project::new(vec![design, build], 1, None).unwrap();
+        project.milestones = vec![Milestone {
+            id: "Done".to_string(),
+            dependencies: vec!["Build".to_string()],
+            ..Default::default()
+        }];
+        let results = run_detailed_simulations(&project, 200).unwrap();
+        let start = NaiveDate::from_ymd_opt(2024, 8, 5).unwrap();
+
+        let calendar = Calendar::new();
+        let html = report(&project, &results, &calendar, start, DisplayUnit::default()).unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.ends_with("</html>\n"));
+        assert_eq!(html.matches("<svg").count(), 5);
+        assert!(html.contains("Design &lt;API&gt;"));
+        assert!(html.contains("<h2>Milestones</h2>"));
+        assert_eq!(html.matches("<th>Skewness</th>").count(), 2);
+        assert!(html.contains("likely estimate: Design &lt;API&gt; &rarr; Build."));
+        // Both tasks are always on the critical path
+        assert_eq!(html.matches("100.0%</td>").count(), 2);
+        // Nothing is loaded from elsewhere
+        assert!(!html.contains("src=") && !html.contains("href="));
+
+        let hours = DisplayUnit::new(Unit::Hours, &calendar).unwrap();
+        let html = report(&project, &results, &calendar, start, hours).unwrap();
+        assert!(html.contains("<th>Percentile</th><th>Hours</th>"));
+        assert!(html.contains(" hours</td>"));
+        assert!(!html.contains("orkdays"));
+    }
+}
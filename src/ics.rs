@@ -0,0 +1,220 @@
+// iCalendar (RFC 5545) export of forecast task and milestone dates
+use crate::{
+    calendar::Calendar,
+    forecast::{finish_date_after, start_date_after, ScheduleForecast},
+    schedule::Project,
+};
+
+use chrono::{NaiveDate, Utc};
+
+use std::fmt::Write;
+
+// Percentiles listed in every event description
+const SPREAD: [f64; 3] = [0.5, 0.85, 0.95];
+
+// Escapes a TEXT property value
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// Folds a content line to at most 75 octets per line, as the spec requires
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+fn ics_date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+fn percent(p: f64) -> String {
+    format!("p{}", (p * 100.0).round())
+}
+
+struct Event {
+    uid: String,
+    summary: String,
+    start: NaiveDate,
+    // Last day of the event, inclusive
+    finish: NaiveDate,
+    description: String,
+    categories: Vec<String>,
+}
+
+// Writes one all-day event per task, spanning the days from its forecast start
+// to its forecast finish at `confidence` (0.0..1.0), and one per milestone on
+// the day it is forecast to be reached. Each description lists the p50, p85
+// and p95 dates.
+pub fn export(
+    project: &Project,
+    forecast: &ScheduleForecast,
+    calendar: &Calendar,
+    start: NaiveDate,
+    confidence: f64,
+) -> Result<String, String> {
+    let mut events = Vec::new();
+    for (task, task_forecast) in project.tasks.iter().zip(&forecast.tasks) {
+        let dates = |p: f64| -> Result<(NaiveDate, NaiveDate), String> {
+            Ok((
+                start_date_after(calendar, start, task_forecast.start(p))?,
+                finish_date_after(calendar, start, task_forecast.finish(p))?,
+            ))
+        };
+        let mut description = format!("Dates shown at {} confidence.", percent(confidence));
+        for p in SPREAD {
+            let (started, finished) = dates(p)?;
+            write!(description, "\n{}: {} to {}", percent(p), started, finished).unwrap();
+        }
+        if let Some(text) = &task.description {
+            write!(description, "\n\n{}", text).unwrap();
+        }
+        let (started, finished) = dates(confidence)?;
+        events.push(Event {
+            uid: format!("task-{}@mcps", task.id),
+            summary: task.display_name().to_string(),
+            start: started,
+            finish: finished,
+            description,
+            categories: task.tags.clone(),
+        });
+    }
+    for (milestone, milestone_forecast) in project.milestones.iter().zip(&forecast.milestones) {
+        let date = |p: f64| finish_date_after(calendar, start, milestone_forecast.finish(p));
+        let mut description = format!("Date shown at {} confidence.", percent(confidence));
+        for p in SPREAD {
+            write!(description, "\n{}: {}", percent(p), date(p)?).unwrap();
+        }
+        let reached = date(confidence)?;
+        events.push(Event {
+            uid: format!("milestone-{}@mcps", milestone.id),
+            summary: format!("Milestone: {}", milestone.display_name()),
+            start: reached,
+            finish: reached,
+            description,
+            categories: Vec::new(),
+        });
+    }
+
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//mcps//Monte Carlo Project Scheduler//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for event in events {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", escape(&event.uid)));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("DTSTART;VALUE=DATE:{}", ics_date(event.start)));
+        // All-day events end on the following day
+        let end = event.finish.succ_opt().ok_or("Date out of range")?;
+        lines.push(format!("DTEND;VALUE=DATE:{}", ics_date(end)));
+        lines.push(format!("SUMMARY:{}", escape(&event.summary)));
+        lines.push(format!("DESCRIPTION:{}", escape(&event.description)));
+        if !event.categories.is_empty() {
+            let categories: Vec<_> = event.categories.iter().map(|c| escape(c)).collect();
+            lines.push(format!("CATEGORIES:{}", categories.join(",")));
+        }
+        // Forecasts shouldn't show anyone as busy
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    Ok(lines.iter().map(|line| fold(line) + "\r\n").collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        schedule::Milestone,
+        simulation::SimulationResult,
+        task::{days_to_duration, Task},
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a,b;c\\d\ne"), "a\\,b\\;c\\\\d\\ne");
+    }
+
+    #[test]
+    fn test_fold() {
+        let line = "x".repeat(160);
+        let folded = fold(&line);
+        let parts: Vec<_> = folded.split("\r\n").collect();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].len(), 75);
+        assert_eq!(parts[1].len(), 75);
+        assert!(parts[1].starts_with(' '));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+
+        // Multi-byte characters are never split
+        let folded = fold(&"é".repeat(40));
+        assert!(folded.split("\r\n").all(|part| part.len() <= 75));
+    }
+
+    #[test]
+    fn test_export() {
+        let mut design = Task::new(
+            "Design",
+            vec![],
+            days_to_duration(1.0),
+            days_to_duration(2.0),
+            days_to_duration(3.0),
+        );
+        design.name = Some("Design, then review".to_string());
+        design.tags = vec!["backend".to_string()];
+        let mut project = Project::new(vec![design], 1, None).unwrap();
+        project.milestones = vec![Milestone {
+            id: "Done".to_string(),
+            dependencies: vec!["Design".to_string()],
+            ..Default::default()
+        }];
+        let results: Vec<_> = (0..=20)
+            .map(|i| {
+                let finish = days_to_duration(1.0 + i as f64 / 10.0);
+                SimulationResult {
+                    total_project_duration: finish,
+                    total_effort_time: finish,
+                    task_start_times: vec![Duration::ZERO],
+                    task_finish_times: vec![finish],
+                    task_effort_times: vec![finish],
+                }
+            })
+            .collect();
+        let forecast = ScheduleForecast::new(&project, &results);
+        let monday = NaiveDate::from_ymd_opt(2024, 8, 5).unwrap();
+
+        let ics = export(&project, &forecast, &Calendar::new(), monday, 0.85).unwrap();
+        let lines: Vec<_> = ics.split("\r\n").collect();
+
+        assert_eq!(lines[0], "BEGIN:VCALENDAR");
+        assert_eq!(lines[lines.len() - 2], "END:VCALENDAR");
+        assert!(lines.contains(&"UID:task-Design@mcps"));
+        assert!(lines.contains(&"SUMMARY:Design\\, then review"));
+        assert!(lines.contains(&"CATEGORIES:backend"));
+        // Work starts Monday and the p85 finish (2.7 days) is Wednesday
+        assert!(lines.contains(&"DTSTART;VALUE=DATE:20240805"));
+        assert!(lines.contains(&"DTEND;VALUE=DATE:20240808"));
+        assert!(lines.contains(&"SUMMARY:Milestone: Done"));
+        assert!(ics.replace("\r\n ", "").contains(
+            "DESCRIPTION:Dates shown at p85 confidence.\\np50: 2024-08-05 to 2024-08-06\\np85: 2024-08-05 to 2024-08-07\\np95: 2024-08-05 to 2024-08-07"
+        ));
+    }
+}
@@ -1,4 +1,19 @@
+pub mod calendar;
+pub mod convergence;
+pub mod forecast;
+pub mod grouping;
+pub mod html;
+pub mod ics;
+pub mod mspdi;
+pub mod plot;
 pub mod schedule;
 pub mod schedule_loader;
 pub mod simulation;
+pub mod sketch;
+pub mod stats;
+pub mod svg;
 pub mod task;
+pub mod terminal;
+pub mod timeline;
+pub mod tracker;
+pub mod units;
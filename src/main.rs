@@ -1,10 +1,32 @@
 use chrono::{NaiveDate, Utc};
-use mcps::{schedule::Project, simulation::run_multiple_simulations};
-
-use clap::{Arg, Command};
-use workdays::WorkCalendar;
-
-use std::{str::FromStr, time::Duration};
+use mcps::{
+    calendar::Calendar,
+    convergence::{run_detailed_until_converged, run_multiple_until_converged, ConvergenceOptions},
+    forecast::{forecast_deadlines, ScheduleForecast},
+    grouping::{forecast_groups, GroupBy},
+    html, ics,
+    mspdi::{self, MspdiImportOptions},
+    plot::{self, Chart, ImageFormat},
+    schedule::Project,
+    schedule_loader::FileFormat,
+    simulation::{
+        run_detailed_simulations_with_options, run_multiple_simulations_with_options,
+        CancellationToken, SimulationOptions,
+    },
+    sketch::{run_sketched_simulations, DEFAULT_K},
+    stats::likely_critical_path,
+    terminal::{
+        self, percentile_steps, CdfOptions, ColorChoice, GlyphChoice, HistogramOptions, Style,
+    },
+    tracker::{self, PointsTable, Tracker, TrackerImportOptions},
+    units::{Amount, DisplayUnit, Unit},
+};
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use indicatif::{HumanCount, ProgressBar, ProgressState, ProgressStyle};
+use terminal_size::{terminal_size, Width};
+
+use std::{io::IsTerminal, str::FromStr, sync::Arc, time::Duration};
 
 const AFTER_HELP_TEXT: &str = "\
 Find example project definition and schedule config files in the repository:
@@ -13,31 +35,98 @@ Find example project definition and schedule config files in the repository:
 
 ";
 
+// Quantiles taken from sketches to draw the charts from
+const SKETCH_SAMPLE_POINTS: usize = 10_001;
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let matches = Command::new("Monte Carlo Project Scheduler")
         .version("0.3.0")
         .author("Stephen Waits <steve@waits.net>")
         .about("Runs Monte Carlo simulations on project schedules")
-        .arg(
-            Arg::new("filename")
-                .help("Path to the project file (.yaml or .json)")
-                .required(true)
-                .index(1),
+        .args_conflicts_with_subcommands(true)
+        .subcommand_negates_reqs(true)
+        .subcommand(
+            Command::new("import")
+                .about("Converts a plan from another tool into an mcps project")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("mspdi")
+                        .about("Imports a Microsoft Project XML (MSPDI) file")
+                        .arg(
+                            Arg::new("filename")
+                                .help("Path to the MSPDI .xml file")
+                                .required(true)
+                                .index(1),
+                        )
+                        .arg(import_output_arg())
+                        .arg(
+                            Arg::new("min-factor")
+                                .long("min-factor")
+                                .help("Minimum estimate as a fraction of each task's duration")
+                                .value_parser(clap::value_parser!(f64))
+                                .default_value("0.75"),
+                        )
+                        .arg(
+                            Arg::new("max-factor")
+                                .long("max-factor")
+                                .help("Maximum estimate as a multiple of each task's duration")
+                                .value_parser(clap::value_parser!(f64))
+                                .default_value("1.5"),
+                        )
+                        .arg(
+                            Arg::new("links-as-fs")
+                                .long("links-as-fs")
+                                .help("Treat start-to-start, finish-to-finish and start-to-finish links as finish-to-start and drop link lags, instead of failing")
+                                .action(ArgAction::SetTrue),
+                        ),
+                )
+                .subcommand(tracker_import_command(
+                    "jira",
+                    "Imports a Jira issue CSV export",
+                ))
+                .subcommand(tracker_import_command(
+                    "linear",
+                    "Imports a Linear issue CSV export",
+                )),
         )
-        .arg(
-            Arg::new("iterations")
-                .short('i')
-                .long("iterations")
-                .help("Number of iterations to run")
-                .default_value("50000"),
-        )
-        .arg(
-            Arg::new("workers")
-                .short('n')
-                .long("workers")
-                .help("Override `num_workers` specified in project file")
-                .value_name("num_workers"),
+        .subcommand(
+            Command::new("plot")
+                .about("Draws a chart of the simulated schedule as an SVG or PNG image")
+                .arg(project_file_arg())
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("Image file to write, as SVG or PNG by its extension")
+                        .required(true)
+                        .value_parser(|s: &str| {
+                            ImageFormat::from_path(s).map(|format| (s.to_string(), format))
+                        })
+                        .value_name("filename"),
+                )
+                .arg(
+                    Arg::new("chart")
+                        .short('c')
+                        .long("chart")
+                        .help("Completion time CDF, completion time histogram, or a Gantt chart of p10-p90 task dates")
+                        .value_parser(|s: &str| s.parse::<Chart>())
+                        .default_value("cdf")
+                        .value_name("cdf|histogram|gantt"),
+                )
+                .arg(bins_arg("40"))
+                .arg(format_arg())
+                .arg(iterations_arg())
+                .arg(workers_arg())
+                .arg(seed_arg())
+                .arg(threads_arg())
+                .arg(unit_arg()),
         )
+        .arg(project_file_arg())
+        .arg(format_arg())
+        .arg(iterations_arg())
+        .arg(workers_arg())
+        .arg(seed_arg())
+        .arg(threads_arg())
         .arg(
             Arg::new("begin")
                 .short('b')
@@ -52,25 +141,149 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .help("Work schedule config file (.yaml or .json)")
                 .value_name("filename"),
         )
+        .arg(
+            Arg::new("group-by")
+                .short('g')
+                .long("group-by")
+                .help(
+                    "Also report effort and finish date per group of tasks (tag, wbs, wbs:<level>)",
+                )
+                .value_parser(|s: &str| s.parse::<GroupBy>())
+                .value_name("grouping"),
+        )
+        .arg(
+            Arg::new("mspdi")
+                .long("mspdi")
+                .help("Also write the project as MSPDI XML with p50 and p85 task dates")
+                .value_name("filename"),
+        )
+        .arg(
+            Arg::new("html")
+                .long("html")
+                .help("Also write a self-contained HTML report with charts and per-task forecasts")
+                .value_name("filename"),
+        )
+        .arg(
+            Arg::new("ics")
+                .long("ics")
+                .help("Also write task and milestone dates as an iCalendar file")
+                .value_name("filename"),
+        )
+        .arg(
+            Arg::new("confidence")
+                .long("confidence")
+                .help("Confidence level (percent) of the dates written with --ics")
+                .value_parser(|s: &str| match s.parse::<f64>() {
+                    Ok(percent) if percent > 0.0 && percent < 100.0 => Ok(percent / 100.0),
+                    _ => Err("must be a number between 0 and 100"),
+                })
+                .default_value("85")
+                .value_name("percent"),
+        )
+        .arg(
+            Arg::new("percentiles")
+                .long("percentiles")
+                .help("Percentiles to show in the chart, e.g. 10,50,80,90,95,99")
+                .value_parser(parse_percentiles)
+                .conflicts_with("step")
+                .value_name("list"),
+        )
+        .arg(
+            Arg::new("step")
+                .long("step")
+                .help("Show a chart row every this many percent [default: 5]")
+                .value_parser(|s: &str| match s.parse::<f64>() {
+                    Ok(step) if step > 0.0 && step <= 100.0 => Ok(step / 100.0),
+                    _ => Err("must be a number greater than 0 and at most 100"),
+                })
+                .value_name("percent"),
+        )
+        .arg(
+            Arg::new("interval")
+                .long("interval")
+                .help("Lower and upper percentiles of the interval marked in the chart [default: 5,95]")
+                .value_parser(|s: &str| match parse_percentiles(s)?.as_slice() {
+                    &[lower, upper] if lower < upper => Ok((lower, upper)),
+                    _ => Err("must be two increasing percentiles, e.g. 5,95".to_string()),
+                })
+                .value_name("lower,upper"),
+        )
+        .arg(
+            Arg::new("histogram")
+                .long("histogram")
+                .help("Also show a histogram of each distribution, marking its mean, median and mode")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(bins_arg("20"))
+        .arg(unit_arg())
+        .arg(
+            Arg::new("color")
+                .long("color")
+                .help("Color the charts: auto colors them on a terminal unless NO_COLOR is set")
+                .value_parser(|s: &str| s.parse::<ColorChoice>())
+                .default_value("auto")
+                .value_name("auto|always|never"),
+        )
+        .arg(
+            Arg::new("glyphs")
+                .long("glyphs")
+                .help("Draw the charts with Unicode box drawing and block characters or plain ASCII: auto uses ASCII unless writing to a terminal")
+                .value_parser(|s: &str| s.parse::<GlyphChoice>())
+                .default_value("auto")
+                .value_name("auto|unicode|ascii"),
+        )
+        .arg(
+            Arg::new("ascii")
+                .long("ascii")
+                .help("Same as --glyphs ascii")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("glyphs"),
+        )
+        .arg(
+            Arg::new("precision")
+                .long("precision")
+                .help("Keep running batches of --iterations simulations until the p50, p85 and p95 completion times are known to within this much time, in workdays unless given a unit, e.g. 0.5d or 4h")
+                .value_parser(|s: &str| match s.parse::<Amount>() {
+                    Ok(amount) if amount.value > 0.0 && amount.unit != Some(Unit::Points) => {
+                        Ok(amount)
+                    }
+                    _ => Err("must be a positive amount of time, e.g. 0.5d, 4h or 1w"),
+                })
+                .value_name("time"),
+        )
+        .arg(
+            Arg::new("max-iterations")
+                .long("max-iterations")
+                .help("Most iterations to run with --precision")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("10000000"),
+        )
+        .arg(
+            Arg::new("streaming")
+                .long("streaming")
+                .help("Summarize outcomes in quantile sketches instead of keeping them all, to save memory on very large runs (percentiles within about 1% in rank)")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["group-by", "mspdi", "html", "ics", "precision"]),
+        )
         .after_help(AFTER_HELP_TEXT)
         .get_matches();
 
-    let project_path = matches.get_one::<String>("filename").unwrap();
-    let num_simulations: usize = matches.get_one::<String>("iterations").unwrap().parse()?;
+    if let Some(("import", import)) = matches.subcommand() {
+        return match import.subcommand() {
+            Some(("mspdi", args)) => import_mspdi(args),
+            Some((tracker, args)) => import_tracker(tracker.parse()?, args),
+            _ => unreachable!("import requires a subcommand"),
+        };
+    }
 
-    // Ensure iterations is >= 100
-    if num_simulations < 100 {
-        return Err("Iterations must be at least 100.".into());
+    if let Some(("plot", args)) = matches.subcommand() {
+        return plot(args);
     }
 
-    // Load the project
-    let mut project = Project::from_file(project_path)?;
-
-    // Load work schedule if it exists
-    let calendar: WorkCalendar = match matches.get_one::<String>("schedule") {
-        Some(filename) => WorkCalendar::from_str(&std::fs::read_to_string(filename)?)?,
-        None => WorkCalendar::new(),
-    };
+    let num_simulations = iterations(&matches)?;
+    let mut project = load_project(&matches)?;
+    let mut options = simulation_options(&matches);
+    let unit = display_unit(&matches, &project)?;
 
     // Determine the start date (command line > project file > TODAY)
     let start_date = matches
@@ -78,164 +291,502 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
         .or(project.start_date)
         .unwrap_or_else(|| Utc::now().date_naive());
-
-    // Check if workers are overridden by command-line argument
-    if let Some(workers_str) = matches.get_one::<String>("workers") {
-        let workers: usize = workers_str.parse()?;
-        if workers < 1 {
-            return Err("Invalid number of workers, must be 1 or more".into());
+    // Workers' time off is laid out from here
+    project.start_date = Some(start_date);
+
+    let group_by = matches.get_one::<GroupBy>("group-by").copied();
+    let mspdi_path = matches.get_one::<String>("mspdi");
+    let ics_path = matches.get_one::<String>("ics");
+    let html_path = matches.get_one::<String>("html");
+    // Sketches don't keep the per-task finish times deadlines are checked against
+    let has_deadlines =
+        !matches.get_flag("streaming") && project.tasks.iter().any(|t| t.must_finish_by.is_some());
+    let convergence_options = match matches.get_one::<Amount>("precision") {
+        Some(&precision) => {
+            let unit = DisplayUnit::new(precision.unit.unwrap_or(Unit::Days), &project.calendar)?;
+            Some(ConvergenceOptions {
+                batch_size: num_simulations,
+                max_iterations: *matches.get_one::<usize>("max-iterations").unwrap(),
+                ..ConvergenceOptions::new(unit.duration(precision.value))
+            })
         }
-        project.num_workers = workers;
-    }
-
-    // Monte Carlo simulation
-    let (project_durations, effort_times) = run_multiple_simulations(&project, num_simulations);
+        None => None,
+    };
+    let mut convergence = None;
+    let progress = Progress::start(
+        &mut options,
+        convergence_options.is_none().then_some(num_simulations),
+    )?;
+
+    // Monte Carlo simulation, keeping per-task results only when a report needs them
+    let (project_durations, effort_times, group_forecasts, deadlines) = if group_by.is_some()
+        || mspdi_path.is_some()
+        || ics_path.is_some()
+        || html_path.is_some()
+        || has_deadlines
+    {
+        let results = match &convergence_options {
+            Some(convergence_options) => {
+                let (results, converged) =
+                    run_detailed_until_converged(&project, &options, convergence_options)?;
+                convergence = Some(converged);
+                results
+            }
+            None => run_detailed_simulations_with_options(&project, num_simulations, &options)?,
+        };
+        progress.finish(results.len())?;
+        let groups = match group_by {
+            Some(group_by) => forecast_groups(&project.group_tasks(group_by), &results),
+            None => vec![],
+        };
+        let forecast = ScheduleForecast::new(&project, &results);
+        if let Some(path) = mspdi_path {
+            let xml = mspdi::export(&project, &forecast, &project.calendar, start_date)?;
+            std::fs::write(path, xml)?;
+        }
+        if let Some(path) = ics_path {
+            let confidence = *matches.get_one::<f64>("confidence").unwrap();
+            let ics = ics::export(
+                &project,
+                &forecast,
+                &project.calendar,
+                start_date,
+                confidence,
+            )?;
+            std::fs::write(path, ics)?;
+        }
+        if let Some(path) = html_path {
+            let html = html::report(&project, &results, &project.calendar, start_date, unit)?;
+            std::fs::write(path, html)?;
+        }
+        let deadlines = forecast_deadlines(&project, &results);
+        let (durations, efforts) = results
+            .iter()
+            .map(|r| (r.total_project_duration, r.total_effort_time))
+            .unzip();
+        (durations, efforts, groups, deadlines)
+    } else if matches.get_flag("streaming") {
+        let (durations, efforts) =
+            run_sketched_simulations(&project, num_simulations, &options, DEFAULT_K)?;
+        progress.finish(durations.count() as usize)?;
+        (
+            durations.sample(SKETCH_SAMPLE_POINTS),
+            efforts.sample(SKETCH_SAMPLE_POINTS),
+            vec![],
+            vec![],
+        )
+    } else {
+        let (durations, efforts) = match &convergence_options {
+            Some(convergence_options) => {
+                let (results, converged) =
+                    run_multiple_until_converged(&project, &options, convergence_options)?;
+                convergence = Some(converged);
+                results
+            }
+            None => run_multiple_simulations_with_options(&project, num_simulations, &options)?,
+        };
+        progress.finish(durations.len())?;
+        (durations, efforts, vec![], vec![])
+    };
 
     // Results output
-    print_ascii_cdf(
+    let mut chart_options = CdfOptions::default();
+    if let Some(percentiles) = matches.get_one::<Vec<f64>>("percentiles") {
+        chart_options.percentiles = percentiles.clone();
+    }
+    if let Some(step) = matches.get_one::<f64>("step") {
+        chart_options.percentiles = percentile_steps(*step);
+    }
+    if let Some(interval) = matches.get_one::<(f64, f64)>("interval") {
+        chart_options.interval = *interval;
+    }
+    let is_terminal = std::io::stdout().is_terminal();
+    let style = Style {
+        color: matches
+            .get_one::<ColorChoice>("color")
+            .unwrap()
+            .enabled(is_terminal),
+        ascii: matches.get_flag("ascii")
+            || matches
+                .get_one::<GlyphChoice>("glyphs")
+                .unwrap()
+                .ascii(is_terminal),
+    };
+    chart_options.style = style;
+    chart_options.unit = unit;
+    let show_histogram = matches.get_flag("histogram");
+    let mut histogram_options = HistogramOptions {
+        bins: *matches.get_one::<usize>("bins").unwrap(),
+        style,
+        unit,
+        ..Default::default()
+    };
+    if let Some((Width(columns), _)) = terminal_size() {
+        chart_options.fit_width(columns as usize);
+        histogram_options.fit_width(columns as usize);
+    }
+    let print_cdf = |data: &[Duration], title: &str, plan| -> Result<(), String> {
+        print!(
+            "{}",
+            terminal::cdf(data, title, &chart_options, start_date, &project.calendar)?
+        );
+        print!("{}", terminal::summary(data, plan, unit));
+        if show_histogram {
+            println!();
+            print!("{}", terminal::histogram(data, title, &histogram_options));
+        }
+        Ok(())
+    };
+    print_cdf(
         &project_durations,
-        format!(
+        &format!(
             "Completion Time ({} Worker{}, starting {})",
             project.num_workers,
             if project.num_workers == 1 { "" } else { "s" },
             start_date,
-        )
-        .as_str(),
-        &start_date,
-        &calendar,
-    );
+        ),
+        Some(likely_critical_path(&project).duration),
+    )?;
 
     println!();
 
-    print_ascii_cdf(
+    print_cdf(
         &effort_times,
-        format!("Total Work Effort (1 worker, starting {})", start_date).as_str(),
-        &start_date,
-        &calendar,
-    );
+        &format!("Total Work Effort (1 worker, starting {})", start_date),
+        None,
+    )?;
+
+    for group in &group_forecasts {
+        println!();
+
+        print_cdf(
+            &group.finish_times,
+            &format!("`{}` Completion Time", group.name),
+            None,
+        )?;
+
+        println!();
+
+        print_cdf(
+            &group.effort_times,
+            &format!("`{}` Work Effort (1 worker)", group.name),
+            None,
+        )?;
+    }
+
+    if !deadlines.is_empty() {
+        println!();
+        print!("{}", terminal::deadlines(&deadlines));
+    }
+
+    if let (Some(convergence), Some(convergence_options)) = (&convergence, &convergence_options) {
+        println!();
+        print!(
+            "{}",
+            terminal::confidence_bands(convergence, convergence_options.precision, unit)
+        );
+    }
 
     Ok(())
 }
 
-fn print_ascii_cdf(data: &[Duration], title: &str, start: &NaiveDate, calendar: &WorkCalendar) {
-    let mut sorted_data = data.to_vec();
-    sorted_data.sort_unstable();
-    let min = sorted_data[0];
-    let max = *sorted_data.last().unwrap();
-
-    let width = 60; // Total width of the field
-
-    // Calculate padding for the title
-    let padding = (width - title.len()) / 2;
-    let centered_title = format!(
-        "{:padding_left$}{}{:padding_right$}",
-        "",
-        title,
-        "",
-        padding_left = padding,
-        padding_right = width - padding - title.len()
-    );
-
-    println!("────┬────────────────────────────────────────────────────────────┬──────────┬──────────┬──────────");
-    println!("%ile│{}│ Workdays │ Schedule │ Complete  ", centered_title);
-    println!("────┼────────────────────────────────────────────────────────────┼──────────┼──────────┼──────────");
-
-    let bar_width = 60;
-
-    // Calculate bar positions
-    let mut bar_positions = Vec::new();
-    for i in 0..=20 {
-        let lower_percentile = if i == 20 {
-            0.0000000001
-        } else {
-            (95 - i * 5) as f64 / 100.0
-        };
-        let upper_percentile = if i == 0 {
-            0.9999999999
-        } else {
-            (100 - i * 5) as f64 / 100.0
+fn plot(args: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let num_simulations = iterations(args)?;
+    let project = load_project(args)?;
+    let unit = display_unit(args, &project)?;
+    let (path, format) = args.get_one::<(String, ImageFormat)>("output").unwrap();
+    let chart = *args.get_one::<Chart>("chart").unwrap();
+    let bins = *args.get_one::<usize>("bins").unwrap();
+
+    let mut options = simulation_options(args);
+    let progress = Progress::start(&mut options, Some(num_simulations))?;
+    let results = run_detailed_simulations_with_options(&project, num_simulations, &options)?;
+    progress.finish(results.len())?;
+    let svg = plot::render(chart, &project, &results, bins, unit);
+    match format {
+        ImageFormat::Svg => std::fs::write(path, svg)?,
+        ImageFormat::Png => std::fs::write(path, plot::svg_to_png(&svg)?)?,
+    }
+    Ok(())
+}
+
+// Parses a comma-separated list of percentages into fractions
+fn parse_percentiles(list: &str) -> Result<Vec<f64>, String> {
+    list.split(',')
+        .map(|item| match item.trim().parse::<f64>() {
+            Ok(percent) if (0.0..=100.0).contains(&percent) => Ok(percent / 100.0),
+            _ => Err(format!("'{}' is not a percentile between 0 and 100", item)),
+        })
+        .collect()
+}
+
+fn iterations(args: &ArgMatches) -> Result<usize, Box<dyn std::error::Error>> {
+    let num_simulations: usize = args.get_one::<String>("iterations").unwrap().parse()?;
+
+    // Ensure iterations is >= 100
+    if num_simulations < 100 {
+        return Err("Iterations must be at least 100.".into());
+    }
+    Ok(num_simulations)
+}
+
+// Loads the project from a file or stdin on the work schedule, if one is
+// given, applying any --workers override
+fn load_project(args: &ArgMatches) -> Result<Project, Box<dyn std::error::Error>> {
+    let project_path = args.get_one::<String>("filename").unwrap();
+    let format = args.get_one::<FileFormat>("format").copied();
+    let calendar = match args.try_get_one::<String>("schedule").ok().flatten() {
+        Some(filename) => Calendar::from_str(&std::fs::read_to_string(filename)?)?,
+        None => Calendar::new(),
+    };
+    let mut project = if project_path == "-" {
+        let format = format.ok_or("Reading a project from stdin requires --format")?;
+        Project::from_reader(std::io::stdin().lock(), format, calendar)?
+    } else {
+        Project::from_file_with_format(project_path, format, calendar)?
+    };
+
+    // Check if workers are overridden by command-line argument
+    if let Some(workers_str) = args.get_one::<String>("workers") {
+        let workers: usize = workers_str.parse()?;
+        if workers < 1 {
+            return Err("Invalid number of workers, must be 1 or more".into());
+        }
+        project.num_workers = workers;
+        // Named workers no longer match the number of workers
+        project.workers.clear();
+    }
+    Ok(project)
+}
+
+// The --unit to show results in, on the project's work schedule
+fn display_unit(args: &ArgMatches, project: &Project) -> Result<DisplayUnit, String> {
+    match args.get_one::<Unit>("unit") {
+        Some(&unit) => DisplayUnit::new(unit, &project.calendar),
+        None => Ok(DisplayUnit::default()),
+    }
+}
+
+fn simulation_options(args: &ArgMatches) -> SimulationOptions {
+    SimulationOptions {
+        seed: args.get_one::<u64>("seed").copied(),
+        threads: args.get_one::<usize>("threads").copied(),
+        ..Default::default()
+    }
+}
+
+// Progress bar on stderr while the simulation runs, and Ctrl-C to stop it
+// early and report on the iterations that finished
+struct Progress {
+    bar: ProgressBar,
+    cancel: CancellationToken,
+    total: Option<usize>,
+}
+
+impl Progress {
+    // `total` is None when the number of iterations isn't known up front
+    fn start(
+        options: &mut SimulationOptions,
+        total: Option<usize>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let cancel = CancellationToken::default();
+        let token = cancel.clone();
+        ctrlc::set_handler(move || {
+            // A second Ctrl-C doesn't wait for the running iterations
+            if token.is_cancelled() {
+                std::process::exit(130);
+            }
+            token.cancel();
+        })?;
+
+        // Whole iterations per second
+        let rate = |state: &ProgressState, w: &mut dyn std::fmt::Write| {
+            let _ = write!(w, "{}/s", HumanCount(state.per_sec() as u64));
         };
-        let lower_index = (lower_percentile * (sorted_data.len() - 1) as f64).round() as usize;
-        let upper_index = (upper_percentile * (sorted_data.len() - 1) as f64).round() as usize;
-        let mid_duration = (sorted_data[lower_index] + sorted_data[upper_index]) / 2;
-        let days = mid_duration.as_secs_f64() / 86400.0;
-        let normalized_position = (days - min.as_secs_f64() / 86400.0)
-            / (max.as_secs_f64() / 86400.0 - min.as_secs_f64() / 86400.0);
-        let bar_position = (normalized_position * bar_width as f64).round() as usize;
-
-        bar_positions.push((bar_position, days));
-    }
-
-    // Determine the needed shift to center the graph
-    let min_bar_position = bar_positions.iter().map(|(pos, _)| pos).min().unwrap_or(&0);
-    let max_bar_position = bar_positions
-        .iter()
-        .map(|(pos, _)| pos)
-        .max()
-        .unwrap_or(&bar_width);
-    let offset = (bar_width - (max_bar_position - min_bar_position)) / 2;
-
-    for (i, (bar_position, days)) in bar_positions.iter().enumerate() {
-        let (end_date, calendar_duration) =
-            calendar.compute_end_date(*start, *days as i64).unwrap();
-
-        let shifted_bar_position = bar_position + offset;
-
-        let (fg, bg) = if i % 2 == 0 {
-            ('░', '▓')
+        let bar = if !std::io::stderr().is_terminal() {
+            ProgressBar::hidden()
+        } else if let Some(total) = total {
+            ProgressBar::new(total as u64).with_style(
+                ProgressStyle::with_template(
+                    "{wide_bar} {human_pos}/{human_len} iterations, {rate}, ETA {eta}",
+                )?
+                .with_key("rate", rate),
+            )
         } else {
-            ('▒', '█')
+            ProgressBar::new_spinner().with_style(
+                ProgressStyle::with_template("{spinner} {human_pos} iterations, {rate}")?
+                    .with_key("rate", rate),
+            )
         };
+        let counter = bar.clone();
+        options.progress = Some(Arc::new(move |_| counter.inc(1)));
+        options.cancel = Some(cancel.clone());
+        Ok(Progress { bar, cancel, total })
+    }
 
-        let color_code = match 100 - i * 5 {
-            0..=45 => "\x1b[31m",        // Red
-            50..=65 => "\x1b[38;5;173m", // Orange
-            70..=80 => "\x1b[33m",       // Yellow
-            85..=95 => "\x1b[32m",       // Green
-            96..=100 => "\x1b[33m",      // Yellow
-            _ => "\x1b[0m",              // Default (shouldn't happen)
-        };
-        let reset_code = "\x1b[0m";
+    fn finish(&self, finished: usize) -> Result<(), Box<dyn std::error::Error>> {
+        self.bar.finish_and_clear();
+        if self.cancel.is_cancelled() {
+            if finished == 0 {
+                return Err("Interrupted before any iterations finished".into());
+            }
+            let of_total = self
+                .total
+                .map_or(String::new(), |total| format!(" of {}", total));
+            eprintln!(
+                "Interrupted, reporting on the {} iterations{} that finished",
+                finished, of_total
+            );
+        }
+        Ok(())
+    }
+}
 
-        let bar_with_divider: String = (0..bar_width)
-            .map(|j| match j {
-                _ if j == shifted_bar_position => '▮',
-                _ if j < shifted_bar_position => fg,
-                _ => bg,
-            })
-            .collect();
-
-        let trailing = match 100 - i * 5 {
-            95 => "◀━┓",
-            60 => "  ┣━━━━━━━━━━━━┓",
-            55 => "  ┃ 90%        ┃",
-            50 => "  ┃ Confidence ┃",
-            45 => "  ┃ Interval   ┃",
-            40 => "  ┣━━━━━━━━━━━━┛",
-            5 => "◀━┛",
-            6..=94 => "  ┃",
-            _ => "",
-        };
+fn import_mspdi(args: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let options = MspdiImportOptions {
+        min_factor: *args.get_one::<f64>("min-factor").unwrap(),
+        max_factor: *args.get_one::<f64>("max-factor").unwrap(),
+        links_as_fs: args.get_flag("links-as-fs"),
+    };
+    let import = mspdi::import_file(args.get_one::<String>("filename").unwrap(), &options)?;
+    for warning in &import.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    write_imported(&import.project, args)
+}
 
-        println!(
-            "{}{:>4}{}│{}{}{}│{}{:5.0} days{}│{}{:5.0} days{}│{}{}{}{}",
-            color_code,
-            format!("p{}", 100 - i * 5),
-            reset_code,
-            color_code,
-            bar_with_divider,
-            reset_code,
-            color_code,
-            days,
-            reset_code,
-            color_code,
-            calendar_duration.num_days(),
-            reset_code,
-            color_code,
-            end_date,
-            reset_code,
-            trailing
-        );
+fn import_tracker(tracker: Tracker, args: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let mut options = TrackerImportOptions::for_tracker(tracker);
+    for column in args.get_many::<String>("column").unwrap_or_default() {
+        options.columns.set(column)?;
+    }
+    if let Some(path) = args.get_one::<String>("points-table") {
+        options.points = PointsTable::from_file(path)?;
+    }
+
+    let import = tracker::import_file(args.get_one::<String>("filename").unwrap(), &options)?;
+    for warning in &import.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    if !import.unmapped.is_empty() {
+        eprintln!("Issues not imported:");
+        for issue in &import.unmapped {
+            eprintln!("  {}: {}", issue.key, issue.reason);
+        }
+    }
+    write_imported(&import.project, args)
+}
+
+fn write_imported(project: &Project, args: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    match args.get_one::<String>("output") {
+        Some(path) => project.to_file(path)?,
+        None => print!("{}", project.to_string_with_format(FileFormat::Yaml)?),
     }
-    println!("────┴────────────────────────────────────────────────────────────┴──────────┴──────────┴──────────");
+    Ok(())
+}
+
+fn import_output_arg() -> Arg {
+    Arg::new("output")
+        .short('o')
+        .long("output")
+        .help("Project file to write (.yaml, .json, .toml or .csv), instead of YAML on stdout")
+        .value_name("filename")
+}
+
+fn project_file_arg() -> Arg {
+    Arg::new("filename")
+        .help("Path to the project file (.yaml, .json, .toml or .csv), or - for stdin")
+        .required(true)
+        .index(1)
+}
+
+fn format_arg() -> Arg {
+    Arg::new("format")
+        .short('f')
+        .long("format")
+        .help("Project file format, overriding detection from the file extension")
+        .value_parser(|s: &str| s.parse::<FileFormat>())
+        .value_name("yaml|json|toml|csv")
+}
+
+fn iterations_arg() -> Arg {
+    Arg::new("iterations")
+        .short('i')
+        .long("iterations")
+        .help("Number of iterations to run")
+        .default_value("50000")
+}
+
+fn unit_arg() -> Arg {
+    Arg::new("unit")
+        .long("unit")
+        .help("Unit to show results in: hours, days or weeks of the work schedule")
+        .value_parser(|s: &str| match s.parse::<Unit>() {
+            Ok(Unit::Points) => Err("must be hours, days or weeks".to_string()),
+            parsed => parsed,
+        })
+        .value_name("hours|days|weeks")
+}
+
+fn bins_arg(default: &'static str) -> Arg {
+    Arg::new("bins")
+        .long("bins")
+        .help("Number of histogram bins")
+        .value_parser(|s: &str| match s.parse::<usize>() {
+            Ok(bins) if bins > 0 => Ok(bins),
+            _ => Err("must be a whole number greater than 0"),
+        })
+        .default_value(default)
+}
+
+fn workers_arg() -> Arg {
+    Arg::new("workers")
+        .short('n')
+        .long("workers")
+        .help("Override `num_workers` specified in project file, dropping any named `workers`")
+        .value_name("num_workers")
+}
+
+fn seed_arg() -> Arg {
+    Arg::new("seed")
+        .long("seed")
+        .help("Random seed, for results that are the same on every run with the same start date (see --begin)")
+        .value_parser(clap::value_parser!(u64))
+        .value_name("number")
+}
+
+fn threads_arg() -> Arg {
+    Arg::new("threads")
+        .long("threads")
+        .help("Number of threads to simulate with [default: one per CPU]")
+        .value_parser(|s: &str| match s.parse::<usize>() {
+            Ok(threads) if threads >= 1 => Ok(threads),
+            _ => Err("must be a whole number, 1 or more".to_string()),
+        })
+        .value_name("number")
+}
+
+fn tracker_import_command(name: &'static str, about: &'static str) -> Command {
+    Command::new(name)
+        .about(about)
+        .arg(
+            Arg::new("filename")
+                .help("Path to the issue CSV export")
+                .required(true)
+                .index(1),
+        )
+        .arg(import_output_arg())
+        .arg(
+            Arg::new("points-table")
+                .long("points-table")
+                .help("Story points to days table (.yaml, .json or .toml)")
+                .value_name("filename"),
+        )
+        .arg(
+            Arg::new("column")
+                .long("column")
+                .help("Read a field from another CSV column, e.g. points=Story point estimate. Fields: key, summary, description, assignee, labels, blocks, blocked_by, points, three_point")
+                .value_name("field=header")
+                .action(ArgAction::Append),
+        )
 }
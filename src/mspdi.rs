@@ -0,0 +1,686 @@
+// Microsoft Project XML (MSPDI) import and export
+use crate::{
+    calendar::Calendar,
+    forecast::{finish_date_after, start_date_after, ScheduleForecast},
+    schedule::Project,
+    schedule_loader::{EstimateInput, MilestoneInput, ScheduleInput, TaskInput},
+};
+
+use chrono::NaiveDate;
+use quick_xml::escape::escape;
+use serde::Deserialize;
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write,
+    path::Path,
+    time::Duration,
+};
+
+// Custom MS Project fields used for the p85 forecast
+const START1_FIELD_ID: u32 = 188743732;
+const FINISH1_FIELD_ID: u32 = 188743733;
+
+const DEFAULT_MINUTES_PER_DAY: f64 = 480.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MspdiImportOptions {
+    // MSPDI has a single duration per task, which becomes the likely estimate.
+    // The minimum and maximum estimates are that duration scaled by these.
+    pub min_factor: f64,
+    pub max_factor: f64,
+    // mcps dependencies are finish-to-start without lag. Other links fail the
+    // import unless this is set, when they're treated as finish-to-start and
+    // their lag is dropped.
+    pub links_as_fs: bool,
+}
+
+impl Default for MspdiImportOptions {
+    fn default() -> Self {
+        MspdiImportOptions {
+            min_factor: 0.75,
+            max_factor: 1.5,
+            links_as_fs: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MspdiImport {
+    pub project: Project,
+    // Parts of the plan that could only be approximated
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct MspdiDocument {
+    start_date: Option<String>,
+    minutes_per_day: Option<f64>,
+    #[serde(default)]
+    tasks: MspdiTasks,
+    #[serde(default)]
+    resources: MspdiResources,
+    #[serde(default)]
+    assignments: MspdiAssignments,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MspdiTasks {
+    #[serde(rename = "Task", default)]
+    tasks: Vec<MspdiTask>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct MspdiTask {
+    #[serde(rename = "UID")]
+    uid: u32,
+    name: Option<String>,
+    notes: Option<String>,
+    outline_level: Option<u32>,
+    milestone: Option<String>,
+    is_null: Option<String>,
+    duration: Option<String>,
+    #[serde(rename = "PredecessorLink", default)]
+    predecessor_links: Vec<MspdiLink>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct MspdiLink {
+    #[serde(rename = "PredecessorUID")]
+    predecessor_uid: u32,
+    // 0 = finish-to-finish, 1 = finish-to-start, 2 = start-to-finish, 3 = start-to-start
+    #[serde(rename = "Type")]
+    link_type: Option<u8>,
+    link_lag: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MspdiResources {
+    #[serde(rename = "Resource", default)]
+    resources: Vec<MspdiResource>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct MspdiResource {
+    #[serde(rename = "UID")]
+    uid: u32,
+    name: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MspdiAssignments {
+    #[serde(rename = "Assignment", default)]
+    assignments: Vec<MspdiAssignment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MspdiAssignment {
+    #[serde(rename = "TaskUID")]
+    task_uid: u32,
+    #[serde(rename = "ResourceUID")]
+    resource_uid: i64,
+}
+
+fn is_true(flag: &Option<String>) -> bool {
+    matches!(flag.as_deref(), Some("1") | Some("true"))
+}
+
+// Parses an ISO 8601 duration such as `PT16H30M0S` (or `P2DT4H0M0S`) into hours
+fn parse_duration_hours(duration: &str) -> Result<f64, String> {
+    let invalid = || format!("Invalid duration {}", duration);
+    let rest = duration.strip_prefix('P').ok_or_else(invalid)?;
+    let (days, time) = rest.split_once('T').unwrap_or((rest, ""));
+    let mut hours = 0.0;
+    if let Some(days) = days.strip_suffix('D') {
+        hours += days.parse::<f64>().map_err(|_| invalid())? * 24.0;
+    } else if !days.is_empty() {
+        return Err(invalid());
+    }
+    let mut number = String::new();
+    for c in time.chars() {
+        let scale = match c {
+            'H' => 1.0,
+            'M' => 1.0 / 60.0,
+            'S' => 1.0 / 3600.0,
+            _ => {
+                number.push(c);
+                continue;
+            }
+        };
+        hours += number.parse::<f64>().map_err(|_| invalid())? * scale;
+        number.clear();
+    }
+    if !number.is_empty() {
+        return Err(invalid());
+    }
+    Ok(hours)
+}
+
+fn link_type_name(link_type: u8) -> Option<&'static str> {
+    match link_type {
+        0 => Some("finish-to-finish"),
+        1 => Some("finish-to-start"),
+        2 => Some("start-to-finish"),
+        3 => Some("start-to-start"),
+        _ => None,
+    }
+}
+
+pub fn import_file<P: AsRef<Path>>(
+    path: P,
+    options: &MspdiImportOptions,
+) -> Result<MspdiImport, Box<dyn std::error::Error>> {
+    let xml = std::fs::read_to_string(&path)?;
+    import_str(&xml, path.as_ref(), options)
+}
+
+// `source` is only used to name the file in error messages
+pub fn import_str(
+    xml: &str,
+    source: &Path,
+    options: &MspdiImportOptions,
+) -> Result<MspdiImport, Box<dyn std::error::Error>> {
+    if !(options.min_factor > 0.0
+        && options.min_factor <= 1.0
+        && options.max_factor >= 1.0
+        && options.min_factor < options.max_factor)
+    {
+        return Err("Estimate factors must satisfy 0 < min <= 1 <= max, with min < max".into());
+    }
+
+    let document: MspdiDocument = quick_xml::de::from_str(xml)?;
+    let minutes_per_day = document
+        .minutes_per_day
+        .filter(|m| *m > 0.0)
+        .unwrap_or(DEFAULT_MINUTES_PER_DAY);
+    let mut warnings = Vec::new();
+
+    // The outline level 0 task summarises the whole project and blank rows are null
+    let tasks: Vec<_> = document
+        .tasks
+        .tasks
+        .into_iter()
+        .filter(|t| t.outline_level.unwrap_or(1) > 0 && !is_true(&t.is_null))
+        .collect();
+    let label = |task: &MspdiTask| match &task.name {
+        Some(name) => format!("'{}' (UID {})", name, task.uid),
+        None => format!("UID {}", task.uid),
+    };
+
+    let durations: HashMap<u32, f64> = tasks
+        .iter()
+        .map(|task| {
+            let hours = match &task.duration {
+                Some(duration) => parse_duration_hours(duration)?,
+                None => 0.0,
+            };
+            Ok((task.uid, hours * 60.0 / minutes_per_day))
+        })
+        .collect::<Result<_, String>>()?;
+
+    // A task is a summary when the next task is nested beneath it
+    let summaries: HashSet<u32> = tasks
+        .windows(2)
+        .filter(|pair| pair[1].outline_level.unwrap_or(1) > pair[0].outline_level.unwrap_or(1))
+        .map(|pair| pair[0].uid)
+        .collect();
+
+    // Zero length tasks become milestones. The loader only lets milestones
+    // depend on earlier milestones, so milestones depend on the tasks behind
+    // any milestone they follow.
+    let known: HashSet<u32> = tasks.iter().map(|t| t.uid).collect();
+    let milestones: HashMap<u32, Vec<u32>> = tasks
+        .iter()
+        .filter(|t| {
+            !summaries.contains(&t.uid) && (is_true(&t.milestone) || durations[&t.uid] <= 0.0)
+        })
+        .map(|t| {
+            let predecessors = t.predecessor_links.iter().map(|l| l.predecessor_uid);
+            (t.uid, predecessors.filter(|p| known.contains(p)).collect())
+        })
+        .collect();
+    fn resolve(uid: u32, milestones: &HashMap<u32, Vec<u32>>, resolved: &mut Vec<String>) {
+        match milestones.get(&uid) {
+            Some(predecessors) => {
+                for &predecessor in predecessors {
+                    resolve(predecessor, milestones, resolved);
+                }
+            }
+            None => {
+                if !resolved.contains(&uid.to_string()) {
+                    resolved.push(uid.to_string());
+                }
+            }
+        }
+    }
+
+    let resources: HashMap<i64, String> = document
+        .resources
+        .resources
+        .into_iter()
+        .filter_map(|r| r.name.map(|name| (r.uid as i64, name)))
+        .collect();
+    let mut owners: BTreeMap<u32, Vec<&String>> = BTreeMap::new();
+    for assignment in &document.assignments.assignments {
+        if let Some(resource) = resources.get(&assignment.resource_uid) {
+            owners
+                .entry(assignment.task_uid)
+                .or_default()
+                .push(resource);
+        }
+    }
+    let num_workers = owners.values().flatten().collect::<HashSet<_>>().len();
+
+    let mut items = Vec::new();
+    let mut milestone_inputs = Vec::new();
+    for task in &tasks {
+        let mut dependencies = Vec::new();
+        for link in &task.predecessor_links {
+            if !known.contains(&link.predecessor_uid) {
+                warnings.push(format!(
+                    "Ignored link to unknown task UID {} from {}",
+                    link.predecessor_uid,
+                    label(task)
+                ));
+                continue;
+            }
+            let link_type = link.link_type.unwrap_or(1);
+            let Some(type_name) = link_type_name(link_type) else {
+                return Err(format!(
+                    "Unknown link type {} on link from UID {} to {}",
+                    link_type,
+                    link.predecessor_uid,
+                    label(task)
+                )
+                .into());
+            };
+            if link_type != 1 {
+                let message = format!(
+                    "{} link from UID {} to {}",
+                    type_name,
+                    link.predecessor_uid,
+                    label(task)
+                );
+                if !options.links_as_fs {
+                    return Err(format!(
+                        "Can't import {}: only finish-to-start links are supported \
+                         (use --links-as-fs to treat every link as one)",
+                        message
+                    )
+                    .into());
+                }
+                warnings.push(format!("Treated {} as finish-to-start", message));
+            }
+            if link.link_lag.unwrap_or(0) != 0 {
+                let message = format!(
+                    "lag on link from UID {} to {}",
+                    link.predecessor_uid,
+                    label(task)
+                );
+                if !options.links_as_fs {
+                    return Err(format!(
+                        "Can't import {}: links can't have lag \
+                         (use --links-as-fs to drop it)",
+                        message
+                    )
+                    .into());
+                }
+                warnings.push(format!("Ignored {}", message));
+            }
+            if milestones.contains_key(&task.uid) {
+                resolve(link.predecessor_uid, &milestones, &mut dependencies);
+            } else if !dependencies.contains(&link.predecessor_uid.to_string()) {
+                dependencies.push(link.predecessor_uid.to_string());
+            }
+        }
+        if milestones.contains_key(&task.uid) {
+            milestone_inputs.push(MilestoneInput {
+                id: task.uid.to_string(),
+                name: task.name.clone(),
+                dependencies,
+            });
+            continue;
+        }
+
+        let task_owners = owners.get(&task.uid).cloned().unwrap_or_default();
+        if task_owners.len() > 1 {
+            warnings.push(format!(
+                "{} has {} resources assigned; only {} is kept as owner",
+                label(task),
+                task_owners.len(),
+                task_owners[0]
+            ));
+        }
+
+        let days = durations[&task.uid];
+        let estimate = (!summaries.contains(&task.uid)).then_some(EstimateInput {
+            min: (days * options.min_factor).into(),
+            likely: days.into(),
+            max: (days * options.max_factor).into(),
+            unit: None,
+        });
+        let input = TaskInput {
+            id: task.uid.to_string(),
+            name: task.name.clone(),
+            description: task.notes.clone(),
+            owner: task_owners.first().map(|o| o.to_string()),
+            estimate,
+            dependencies,
+            ..Default::default()
+        };
+        items.push((task.outline_level.unwrap_or(1), input));
+    }
+
+    fn nest(
+        items: &mut std::iter::Peekable<std::vec::IntoIter<(u32, TaskInput)>>,
+        level: u32,
+    ) -> Vec<TaskInput> {
+        let mut nested = Vec::new();
+        while let Some((item_level, mut input)) = items.next_if(|(l, _)| *l > level) {
+            input.subtasks = nest(items, item_level);
+            nested.push(input);
+        }
+        nested
+    }
+
+    let input = ScheduleInput {
+        num_workers: Some(num_workers.max(1)),
+        workers: Vec::new(),
+        start_date: document
+            .start_date
+            .and_then(|d| NaiveDate::parse_from_str(d.get(..10)?, "%Y-%m-%d").ok()),
+        unit: None,
+        points: None,
+        focus: None,
+        interruptions: None,
+        include: Vec::new(),
+        tasks: nest(&mut items.into_iter().peekable(), 0),
+        milestones: milestone_inputs,
+    };
+    let project = Project::from_input(input, source, Vec::new(), Calendar::new())?;
+
+    Ok(MspdiImport { project, warnings })
+}
+
+// A task, WBS node or milestone in outline order
+struct ExportItem<'a> {
+    input: &'a TaskInput,
+    level: usize,
+    uid: u32,
+    // Index into `Project::milestones`
+    milestone: Option<usize>,
+}
+
+fn outline<'a>(inputs: &'a [TaskInput], level: usize, items: &mut Vec<ExportItem<'a>>) {
+    for input in inputs {
+        items.push(ExportItem {
+            input,
+            level,
+            uid: 0,
+            milestone: None,
+        });
+        outline(&input.subtasks, level + 1, items);
+    }
+}
+
+fn format_hours(days: f64) -> String {
+    let minutes = (days * DEFAULT_MINUTES_PER_DAY).round() as u64;
+    format!("PT{}H{}M0S", minutes / 60, minutes % 60)
+}
+
+// Writes the project as MSPDI with each task's p50 start and finish as its
+// dates, and the p85 dates in the Start1 and Finish1 custom fields. Numeric
+// task ids (as produced by `import_file`) are kept as UIDs.
+pub fn export(
+    project: &Project,
+    forecast: &ScheduleForecast,
+    calendar: &Calendar,
+    start: NaiveDate,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let input = ScheduleInput::from(project);
+    let milestones: Vec<TaskInput> = input
+        .milestones
+        .iter()
+        .map(|m| TaskInput {
+            id: m.id.clone(),
+            name: m.name.clone(),
+            dependencies: m.dependencies.clone(),
+            ..Default::default()
+        })
+        .collect();
+    let mut items = Vec::new();
+    outline(&input.tasks, 1, &mut items);
+    items.extend(
+        milestones
+            .iter()
+            .enumerate()
+            .map(|(index, input)| ExportItem {
+                input,
+                level: 1,
+                uid: 0,
+                milestone: Some(index),
+            }),
+    );
+
+    let mut used: HashSet<u32> = HashSet::new();
+    for item in &mut items {
+        if let Ok(uid) = item.input.id.parse::<u32>() {
+            if uid > 0 && used.insert(uid) {
+                item.uid = uid;
+            }
+        }
+    }
+    let fresh_uids = used.iter().max().copied().unwrap_or(0) + 1..;
+    for (item, uid) in items.iter_mut().filter(|i| i.uid == 0).zip(fresh_uids) {
+        item.uid = uid;
+    }
+    let uids: HashMap<&str, u32> = items.iter().map(|i| (i.input.id.as_str(), i.uid)).collect();
+    let task_index: HashMap<&str, usize> = project
+        .tasks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (t.id.as_str(), i))
+        .collect();
+
+    // Forecast elapsed workdays per item, rolling summaries up from their tasks
+    let forecasts = &forecast.tasks;
+    let elapsed = |item: &ExportItem, p: f64| -> (Duration, Duration) {
+        if let Some(index) = item.milestone {
+            let reached = forecast.milestones[index].finish(p);
+            return (reached, reached);
+        }
+        let leaves: Vec<usize> = match task_index.get(item.input.id.as_str()) {
+            Some(&index) => vec![index],
+            None => project
+                .tasks
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| {
+                    project
+                        .wbs_ancestors(t.parent.as_deref())
+                        .contains(&item.input.id.as_str())
+                })
+                .map(|(i, _)| i)
+                .collect(),
+        };
+        let start = leaves.iter().map(|&i| forecasts[i].start(p)).min();
+        let finish = leaves.iter().map(|&i| forecasts[i].finish(p)).max();
+        (start.unwrap_or_default(), finish.unwrap_or_default())
+    };
+    let dates = |item: &ExportItem, p: f64| -> Result<(String, String), String> {
+        let (started, finished) = elapsed(item, p);
+        let finish = format!("{}T17:00:00", finish_date_after(calendar, start, finished)?);
+        if item.milestone.is_some() {
+            return Ok((finish.clone(), finish));
+        }
+        Ok((
+            format!("{}T08:00:00", start_date_after(calendar, start, started)?),
+            finish,
+        ))
+    };
+
+    let mut resources: Vec<&str> = Vec::new();
+    for task in &project.tasks {
+        if let Some(owner) = task.owner.as_deref() {
+            if !resources.contains(&owner) {
+                resources.push(owner);
+            }
+        }
+    }
+
+    let mut project_finish = String::new();
+    let mut tasks_xml = String::new();
+    for (id, item) in items.iter().enumerate() {
+        let (p50_start, p50_finish) = dates(item, 0.5)?;
+        let (p85_start, p85_finish) = dates(item, 0.85)?;
+        project_finish = project_finish.max(p50_finish.clone());
+        let summary = !item.input.subtasks.is_empty();
+
+        writeln!(tasks_xml, "    <Task>")?;
+        writeln!(tasks_xml, "      <UID>{}</UID>", item.uid)?;
+        writeln!(tasks_xml, "      <ID>{}</ID>", id + 1)?;
+        writeln!(
+            tasks_xml,
+            "      <Name>{}</Name>",
+            escape(item.input.name.as_deref().unwrap_or(&item.input.id))
+        )?;
+        writeln!(
+            tasks_xml,
+            "      <OutlineLevel>{}</OutlineLevel>",
+            item.level
+        )?;
+        writeln!(tasks_xml, "      <Summary>{}</Summary>", summary as u8)?;
+        if item.milestone.is_some() {
+            writeln!(tasks_xml, "      <Milestone>1</Milestone>")?;
+            writeln!(tasks_xml, "      <Duration>PT0H0M0S</Duration>")?;
+        }
+        writeln!(tasks_xml, "      <Start>{}</Start>", p50_start)?;
+        writeln!(tasks_xml, "      <Finish>{}</Finish>", p50_finish)?;
+        if let Some(estimate) = &item.input.estimate {
+            writeln!(
+                tasks_xml,
+                "      <Duration>{}</Duration>",
+                format_hours(estimate.likely.value)
+            )?;
+        }
+        if let Some(notes) = &item.input.description {
+            writeln!(tasks_xml, "      <Notes>{}</Notes>", escape(notes))?;
+        }
+        for dep in &item.input.dependencies {
+            writeln!(tasks_xml, "      <PredecessorLink>")?;
+            writeln!(
+                tasks_xml,
+                "        <PredecessorUID>{}</PredecessorUID>",
+                uids[dep.as_str()]
+            )?;
+            writeln!(tasks_xml, "        <Type>1</Type>")?;
+            writeln!(tasks_xml, "      </PredecessorLink>")?;
+        }
+        for (field_id, value) in [(START1_FIELD_ID, p85_start), (FINISH1_FIELD_ID, p85_finish)] {
+            writeln!(tasks_xml, "      <ExtendedAttribute>")?;
+            writeln!(tasks_xml, "        <FieldID>{}</FieldID>", field_id)?;
+            writeln!(tasks_xml, "        <Value>{}</Value>", value)?;
+            writeln!(tasks_xml, "      </ExtendedAttribute>")?;
+        }
+        writeln!(tasks_xml, "    </Task>")?;
+    }
+
+    let mut xml = String::new();
+    writeln!(
+        xml,
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#
+    )?;
+    writeln!(
+        xml,
+        r#"<Project xmlns="http://schemas.microsoft.com/project">"#
+    )?;
+    writeln!(xml, "  <SaveVersion>14</SaveVersion>")?;
+    writeln!(xml, "  <StartDate>{}T08:00:00</StartDate>", start)?;
+    writeln!(xml, "  <FinishDate>{}</FinishDate>", project_finish)?;
+    writeln!(
+        xml,
+        "  <MinutesPerDay>{}</MinutesPerDay>",
+        DEFAULT_MINUTES_PER_DAY
+    )?;
+    writeln!(xml, "  <ExtendedAttributes>")?;
+    for (field_id, field_name, alias) in [
+        (START1_FIELD_ID, "Start1", "p85 Start"),
+        (FINISH1_FIELD_ID, "Finish1", "p85 Finish"),
+    ] {
+        writeln!(xml, "    <ExtendedAttribute>")?;
+        writeln!(xml, "      <FieldID>{}</FieldID>", field_id)?;
+        writeln!(xml, "      <FieldName>{}</FieldName>", field_name)?;
+        writeln!(xml, "      <Alias>{}</Alias>", alias)?;
+        writeln!(xml, "    </ExtendedAttribute>")?;
+    }
+    writeln!(xml, "  </ExtendedAttributes>")?;
+    writeln!(xml, "  <Tasks>")?;
+    xml.push_str(&tasks_xml);
+    writeln!(xml, "  </Tasks>")?;
+    writeln!(xml, "  <Resources>")?;
+    for (i, resource) in resources.iter().enumerate() {
+        writeln!(xml, "    <Resource>")?;
+        writeln!(xml, "      <UID>{}</UID>", i + 1)?;
+        writeln!(xml, "      <ID>{}</ID>", i + 1)?;
+        writeln!(xml, "      <Name>{}</Name>", escape(*resource))?;
+        writeln!(xml, "    </Resource>")?;
+    }
+    writeln!(xml, "  </Resources>")?;
+    writeln!(xml, "  <Assignments>")?;
+    let mut assignment_uid = 0;
+    for item in &items {
+        let Some(owner) = item.input.owner.as_deref() else {
+            continue;
+        };
+        assignment_uid += 1;
+        let resource_uid = resources.iter().position(|r| *r == owner).unwrap() + 1;
+        writeln!(xml, "    <Assignment>")?;
+        writeln!(xml, "      <UID>{}</UID>", assignment_uid)?;
+        writeln!(xml, "      <TaskUID>{}</TaskUID>", item.uid)?;
+        writeln!(xml, "      <ResourceUID>{}</ResourceUID>", resource_uid)?;
+        writeln!(xml, "    </Assignment>")?;
+    }
+    writeln!(xml, "  </Assignments>")?;
+    writeln!(xml, "</Project>")?;
+
+    Ok(xml)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration_hours() {
+        assert_eq!(parse_duration_hours("PT16H0M0S"), Ok(16.0));
+        assert_eq!(parse_duration_hours("PT7H30M0S"), Ok(7.5));
+        assert_eq!(parse_duration_hours("PT0H0M0S"), Ok(0.0));
+        assert_eq!(parse_duration_hours("P1DT2H0M0S"), Ok(26.0));
+        assert!(parse_duration_hours("16H").is_err());
+        assert!(parse_duration_hours("PT16").is_err());
+    }
+
+    #[test]
+    fn test_format_hours() {
+        assert_eq!(format_hours(2.0), "PT16H0M0S");
+        assert_eq!(format_hours(0.5625), "PT4H30M0S");
+    }
+
+    #[test]
+    fn test_invalid_factors() {
+        let options = MspdiImportOptions {
+            min_factor: 1.0,
+            max_factor: 1.0,
+            ..Default::default()
+        };
+        let result = import_str("<Project/>", Path::new("plan.xml"), &options);
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .starts_with("Estimate factors must satisfy"));
+    }
+}
@@ -0,0 +1,129 @@
+// Charts written to SVG or PNG files by `mcps plot`
+use crate::{
+    forecast::forecast_tasks, schedule::Project, simulation::SimulationResult, svg,
+    units::DisplayUnit,
+};
+
+use resvg::{
+    tiny_skia::{Pixmap, Transform},
+    usvg,
+};
+
+use std::{path::Path, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chart {
+    Cdf,
+    Histogram,
+    Gantt,
+}
+
+impl FromStr for Chart {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "cdf" => Ok(Chart::Cdf),
+            "histogram" | "pdf" => Ok(Chart::Histogram),
+            "gantt" => Ok(Chart::Gantt),
+            _ => Err(format!(
+                "Unsupported chart '{}'. Use 'cdf', 'histogram' or 'gantt'",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Svg,
+    Png,
+}
+
+impl ImageFormat {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(std::ffi::OsStr::to_str)
+            .unwrap_or_default();
+        match extension.to_lowercase().as_str() {
+            "svg" => Ok(ImageFormat::Svg),
+            "png" => Ok(ImageFormat::Png),
+            _ => Err("Unsupported image format. Use .svg or .png".to_string()),
+        }
+    }
+}
+
+// Draws the chart as SVG, with `bins` used by the histogram and times shown
+// in `unit`
+pub fn render(
+    chart: Chart,
+    project: &Project,
+    results: &[SimulationResult],
+    bins: usize,
+    unit: DisplayUnit,
+) -> String {
+    let mut durations: Vec<_> = results.iter().map(|r| r.total_project_duration).collect();
+    durations.sort_unstable();
+    let workers = format!(
+        "{} Worker{}",
+        project.num_workers,
+        if project.num_workers == 1 { "" } else { "s" }
+    );
+    match chart {
+        Chart::Cdf => svg::cdf(&durations, &format!("Completion Time ({})", workers), unit),
+        Chart::Histogram => svg::histogram(
+            &durations,
+            bins,
+            &format!("Completion Time Distribution ({})", workers),
+            unit,
+        ),
+        Chart::Gantt => svg::gantt(
+            project,
+            &forecast_tasks(project, results),
+            &format!("Task Forecast ({})", workers),
+            unit,
+        ),
+    }
+}
+
+// Bundled so charts render the same wherever they're drawn
+const FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSans.ttf");
+const FONT_FAMILY: &str = "DejaVu Sans";
+
+// Rasterizes an SVG chart. Text uses the bundled font rather than whatever
+// the system has, so the image is identical on every machine.
+pub fn svg_to_png(svg: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut options = usvg::Options::default();
+    let fonts = options.fontdb_mut();
+    fonts.load_font_data(FONT.to_vec());
+    fonts.set_sans_serif_family(FONT_FAMILY);
+
+    let tree = usvg::Tree::from_str(svg, &options)?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = Pixmap::new(size.width(), size.height()).ok_or("Chart has no area")?;
+    resvg::render(&tree, Transform::default(), &mut pixmap.as_mut());
+    Ok(pixmap.encode_png()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chart() {
+        assert_eq!("CDF".parse::<Chart>(), Ok(Chart::Cdf));
+        assert_eq!("pdf".parse::<Chart>(), Ok(Chart::Histogram));
+        assert_eq!("gantt".parse::<Chart>(), Ok(Chart::Gantt));
+        assert!("pie".parse::<Chart>().is_err());
+    }
+
+    #[test]
+    fn test_image_format() {
+        assert_eq!(ImageFormat::from_path("a/chart.svg"), Ok(ImageFormat::Svg));
+        assert_eq!(ImageFormat::from_path("chart.PNG"), Ok(ImageFormat::Png));
+        assert!(ImageFormat::from_path("chart.jpg").is_err());
+        assert!(ImageFormat::from_path("chart").is_err());
+    }
+}
@@ -1,6 +1,6 @@
-use crate::task::Task;
+use crate::{calendar::Calendar, task::Task};
 
-use chrono::NaiveDate;
+use chrono::{NaiveDate, Weekday};
 
 use std::collections::{HashMap, HashSet};
 use std::time::Duration;
@@ -10,6 +10,117 @@ pub struct Project {
     pub tasks: Vec<Task>,
     pub num_workers: usize,
     pub start_date: Option<NaiveDate>,
+    // Summary nodes of the work breakdown structure, parents listed before children
+    pub wbs: Vec<WbsNode>,
+    pub milestones: Vec<Milestone>,
+    // Named workers, one per worker when given
+    pub workers: Vec<Worker>,
+    // Working days and holidays, Monday to Friday unless set from a work
+    // schedule config file
+    pub calendar: Calendar,
+    // Share of their time workers spend on planned work, unless a worker has
+    // their own
+    pub focus: Focus,
+    pub interruptions: Option<Interruptions>,
+}
+
+// Share of a worker's time spent on planned work, drawn afresh each iteration
+// between `min` and `max`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Focus {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Focus {
+    pub const FULL: Focus = Focus::fixed(1.0);
+
+    pub const fn fixed(focus: f64) -> Self {
+        Focus {
+            min: focus,
+            max: focus,
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        0.0 < self.min && self.min <= self.max && self.max <= 1.0
+    }
+}
+
+// Unplanned work that takes workers away from their tasks, arriving at random
+// `rate` times per workday on average, each taking between `min_time` and
+// `max_time`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interruptions {
+    pub rate: f64,
+    pub min_time: Duration,
+    pub likely_time: Duration,
+    pub max_time: Duration,
+}
+
+// A named member of the team. Tasks whose `owner` is a worker's name are only
+// ever given to that worker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Worker {
+    pub name: String,
+    // Share of each working day spent on this project, above 0 and up to 1
+    pub allocation: f64,
+    // Days of the week they work, when not the project calendar's
+    pub work_days: Option<Vec<Weekday>>,
+    // Days away, as inclusive ranges
+    pub time_off: Vec<(NaiveDate, NaiveDate)>,
+    // Their own share of time on planned work, instead of the project's
+    pub focus: Option<Focus>,
+}
+
+impl Worker {
+    pub fn new(name: &str) -> Self {
+        Worker {
+            name: name.to_string(),
+            allocation: 1.0,
+            work_days: None,
+            time_off: Vec::new(),
+            focus: None,
+        }
+    }
+
+    // Works whenever the project calendar does
+    pub fn is_always_available(&self) -> bool {
+        self.allocation == 1.0 && self.work_days.is_none() && self.time_off.is_empty()
+    }
+
+    pub fn is_away(&self, date: NaiveDate) -> bool {
+        self.time_off
+            .iter()
+            .any(|&(first, last)| first <= date && date <= last)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WbsNode {
+    pub id: String,
+    pub name: Option<String>,
+    pub parent: Option<String>,
+}
+
+impl WbsNode {
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.id)
+    }
+}
+
+// A point in the schedule reached once all of its dependencies have finished
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Milestone {
+    pub id: String,
+    pub name: Option<String>,
+    pub dependencies: Vec<String>,
+}
+
+impl Milestone {
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.id)
+    }
 }
 
 impl Project {
@@ -22,6 +133,12 @@ impl Project {
             tasks,
             num_workers,
             start_date,
+            wbs: Vec::new(),
+            milestones: Vec::new(),
+            workers: Vec::new(),
+            calendar: Calendar::new(),
+            focus: Focus::FULL,
+            interruptions: None,
         };
         schedule.validate()?;
         Ok(schedule)
@@ -39,21 +156,173 @@ impl Project {
             if task.min_time > task.max_time {
                 return Err(format!(
                     "Minimum duration greater than maximum for task {}",
-                    task.id
+                    task.describe()
                 ));
             }
             if task.min_time <= Duration::from_secs(0) || task.max_time <= Duration::from_secs(0) {
-                return Err(format!("Invalid task duration for task {}", task.id));
+                return Err(format!(
+                    "Invalid task duration for task {}",
+                    task.describe()
+                ));
+            }
+            if task.fixed_start.is_some() && task.fixed_finish.is_some() {
+                return Err(format!(
+                    "Task {} has both a fixed start and a fixed finish",
+                    task.describe()
+                ));
             }
             for dep in &task.dependencies {
                 if !all_task_ids.contains(dep) {
-                    return Err(format!("Missing dependency {} for task {}", dep, task.id));
+                    return Err(format!(
+                        "Missing dependency {} for task {}",
+                        dep,
+                        task.describe()
+                    ));
                 }
             }
         }
+        self.check_wbs()?;
+        self.check_milestones()?;
+        self.check_workers()?;
+        self.check_focus()?;
         self.check_cyclic_dependencies()
     }
 
+    // Ids of the WBS nodes containing `parent`, from `parent` itself up to the root
+    pub fn wbs_ancestors<'a>(&'a self, parent: Option<&'a str>) -> Vec<&'a str> {
+        let mut ancestors = Vec::new();
+        let mut current = parent;
+        while let Some(id) = current {
+            ancestors.push(id);
+            current = self
+                .wbs
+                .iter()
+                .find(|n| n.id == id)
+                .and_then(|n| n.parent.as_deref());
+        }
+        ancestors
+    }
+
+    fn check_wbs(&self) -> Result<(), String> {
+        let mut known = HashSet::new();
+        for node in &self.wbs {
+            if let Some(parent) = &node.parent {
+                if !known.contains(parent) {
+                    return Err(format!("Missing parent {} for task {}", parent, node.id));
+                }
+            }
+            known.insert(&node.id);
+        }
+        for task in &self.tasks {
+            if known.contains(&task.id) {
+                return Err(format!("Duplicate task id {}", task.describe()));
+            }
+            if let Some(parent) = &task.parent {
+                if !known.contains(parent) {
+                    return Err(format!(
+                        "Missing parent {} for task {}",
+                        parent,
+                        task.describe()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_milestones(&self) -> Result<(), String> {
+        let mut ids: HashSet<&String> = self.tasks.iter().map(|t| &t.id).collect();
+        ids.extend(self.wbs.iter().map(|n| &n.id));
+        for milestone in &self.milestones {
+            if !ids.insert(&milestone.id) {
+                return Err(format!("Duplicate milestone id {}", milestone.id));
+            }
+            for dep in &milestone.dependencies {
+                if !self.tasks.iter().any(|t| &t.id == dep) {
+                    return Err(format!(
+                        "Missing dependency {} for milestone {}",
+                        dep, milestone.id
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_workers(&self) -> Result<(), String> {
+        if !self.workers.is_empty() && self.workers.len() != self.num_workers {
+            return Err(format!(
+                "Project lists {} workers but has num_workers {}",
+                self.workers.len(),
+                self.num_workers
+            ));
+        }
+        let mut names = HashSet::new();
+        for worker in &self.workers {
+            if !names.insert(&worker.name) {
+                return Err(format!("Duplicate worker {}", worker.name));
+            }
+            if !(worker.allocation > 0.0 && worker.allocation <= 1.0) {
+                return Err(format!(
+                    "Allocation of worker {} must be above 0% and at most 100%",
+                    worker.name
+                ));
+            }
+            if worker.work_days.as_ref().is_some_and(Vec::is_empty) {
+                return Err(format!("Worker {} has no work days", worker.name));
+            }
+            if let Some(&(first, last)) = worker.time_off.iter().find(|(f, l)| f > l) {
+                return Err(format!(
+                    "Time off for worker {} ends ({}) before it starts ({})",
+                    worker.name, last, first
+                ));
+            }
+        }
+        if !self.workers.is_empty() {
+            let owned = self
+                .tasks
+                .iter()
+                .filter_map(|task| Some((task, task.owner.as_ref()?)));
+            for (task, owner) in owned {
+                if !names.contains(owner) {
+                    return Err(format!(
+                        "Owner {} of task {} is not a listed worker",
+                        owner,
+                        task.describe()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_focus(&self) -> Result<(), String> {
+        let invalid = "must be above 0 and at most 1, with min no more than max";
+        if !self.focus.is_valid() {
+            return Err(format!("Focus {}", invalid));
+        }
+        for worker in &self.workers {
+            if worker.focus.is_some_and(|focus| !focus.is_valid()) {
+                return Err(format!("Focus of worker {} {}", worker.name, invalid));
+            }
+        }
+        if let Some(interruptions) = &self.interruptions {
+            if !(interruptions.rate.is_finite() && interruptions.rate >= 0.0) {
+                return Err("Interruption rate must be 0 or more per workday".to_string());
+            }
+            if !(interruptions.min_time <= interruptions.likely_time
+                && interruptions.likely_time <= interruptions.max_time
+                && interruptions.min_time < interruptions.max_time)
+            {
+                return Err(
+                    "Invalid interruption duration, expected min <= likely <= max and min < max"
+                        .to_string(),
+                );
+            }
+        }
+        Ok(())
+    }
+
     fn check_cyclic_dependencies(&self) -> Result<(), String> {
         let mut visited = HashSet::new();
         let mut stack = HashSet::new();
@@ -72,9 +341,12 @@ impl Project {
         stack: &mut HashSet<&'a str>,
     ) -> Result<(), String> {
         if stack.contains(task_id) {
+            let task = task_map
+                .get(&task_id.to_string())
+                .map_or(task_id.to_string(), |t| t.describe());
             return Err(format!(
                 "Cyclic dependency detected involving task {}",
-                task_id
+                task
             ));
         }
         if visited.contains(task_id) {
@@ -103,6 +375,7 @@ mod tests {
             min_time: Duration::from_secs(min),
             likely_time: Duration::from_secs(likely),
             max_time: Duration::from_secs(max),
+            ..Default::default()
         }
     }
 
@@ -205,6 +478,93 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_wbs_ancestors() {
+        let mut task = create_task("Subtask", 1, 2, 3, vec![]);
+        task.parent = Some("Story".to_string());
+        let schedule = Project {
+            tasks: vec![task],
+            num_workers: 1,
+            start_date: None,
+            workers: vec![],
+            calendar: Calendar::new(),
+            focus: Focus::FULL,
+            interruptions: None,
+            wbs: vec![
+                WbsNode {
+                    id: "Epic".to_string(),
+                    ..Default::default()
+                },
+                WbsNode {
+                    id: "Story".to_string(),
+                    parent: Some("Epic".to_string()),
+                    ..Default::default()
+                },
+            ],
+            milestones: Vec::new(),
+        };
+        assert!(schedule.validate().is_ok());
+
+        assert_eq!(
+            schedule.wbs_ancestors(schedule.tasks[0].parent.as_deref()),
+            vec!["Story", "Epic"]
+        );
+        assert!(schedule.wbs_ancestors(None).is_empty());
+    }
+
+    #[test]
+    fn test_missing_wbs_parent() {
+        let mut task = create_task("Subtask", 1, 2, 3, vec![]);
+        task.parent = Some("Story".to_string());
+        let schedule = Project::new(vec![task], 1, None);
+        assert_eq!(
+            schedule.unwrap_err(),
+            "Missing parent Story for task Subtask"
+        );
+    }
+
+    #[test]
+    fn test_errors_name_source_file() {
+        let mut a = create_task("A", 1, 2, 3, vec!["B"]);
+        a.source = Some("frontend.yaml".into());
+        let mut b = create_task("B", 2, 3, 4, vec!["A"]);
+        b.source = Some("backend.yaml".into());
+        let schedule = Project::new(vec![a.clone(), b], 1, None);
+        let err = schedule.unwrap_err();
+        assert!(
+            err.ends_with("(in frontend.yaml)") || err.ends_with("(in backend.yaml)"),
+            "Unexpected error message: {}",
+            err
+        );
+
+        a.dependencies = vec!["C".to_string()];
+        let schedule = Project::new(vec![a], 1, None);
+        assert_eq!(
+            schedule.unwrap_err(),
+            "Missing dependency C for task A (in frontend.yaml)"
+        );
+    }
+
+    #[test]
+    fn test_milestone_errors() {
+        let mut schedule = Project::new(vec![create_task("A", 1, 2, 3, vec![])], 1, None).unwrap();
+        schedule.milestones = vec![Milestone {
+            id: "Beta".to_string(),
+            dependencies: vec!["A".to_string()],
+            ..Default::default()
+        }];
+        assert!(schedule.validate().is_ok());
+
+        schedule.milestones[0].dependencies.push("B".to_string());
+        assert_eq!(
+            schedule.validate().unwrap_err(),
+            "Missing dependency B for milestone Beta"
+        );
+
+        schedule.milestones[0].id = "A".to_string();
+        assert_eq!(schedule.validate().unwrap_err(), "Duplicate milestone id A");
+    }
+
     #[test]
     fn test_self_dependency() {
         let tasks = vec![create_task("A", 1, 2, 3, vec!["A"])];
@@ -221,4 +581,119 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_fixed_start_and_finish() {
+        let mut task = create_task("A", 1, 2, 3, vec![]);
+        task.fixed_start = NaiveDate::from_ymd_opt(2026, 11, 2);
+        task.fixed_finish = NaiveDate::from_ymd_opt(2026, 11, 6);
+        assert_eq!(
+            Project::new(vec![task], 1, None).unwrap_err(),
+            "Task A has both a fixed start and a fixed finish"
+        );
+    }
+
+    #[test]
+    fn test_worker_errors() {
+        let tasks = vec![create_task("A", 1, 2, 3, vec![])];
+        let mut schedule = Project::new(tasks, 2, None).unwrap();
+        schedule.workers = vec![Worker::new("Ann"), Worker::new("Bo")];
+        assert!(schedule.validate().is_ok());
+
+        schedule.num_workers = 3;
+        assert_eq!(
+            schedule.validate().unwrap_err(),
+            "Project lists 2 workers but has num_workers 3"
+        );
+        schedule.num_workers = 2;
+
+        schedule.workers[1].name = "Ann".to_string();
+        assert_eq!(schedule.validate().unwrap_err(), "Duplicate worker Ann");
+        schedule.workers[1].name = "Bo".to_string();
+
+        schedule.workers[1].allocation = 0.0;
+        assert_eq!(
+            schedule.validate().unwrap_err(),
+            "Allocation of worker Bo must be above 0% and at most 100%"
+        );
+        schedule.workers[1].allocation = 0.5;
+
+        schedule.workers[1].work_days = Some(vec![]);
+        assert_eq!(
+            schedule.validate().unwrap_err(),
+            "Worker Bo has no work days"
+        );
+        schedule.workers[1].work_days = None;
+
+        let date = |d| NaiveDate::from_ymd_opt(2026, 11, d).unwrap();
+        schedule.workers[0].time_off = vec![(date(9), date(6))];
+        assert_eq!(
+            schedule.validate().unwrap_err(),
+            "Time off for worker Ann ends (2026-11-06) before it starts (2026-11-09)"
+        );
+        schedule.workers[0].time_off = vec![(date(6), date(9))];
+        assert!(schedule.validate().is_ok());
+
+        schedule.tasks[0].owner = Some("Cy".to_string());
+        assert_eq!(
+            schedule.validate().unwrap_err(),
+            "Owner Cy of task A is not a listed worker"
+        );
+        schedule.tasks[0].owner = Some("Bo".to_string());
+        assert!(schedule.validate().is_ok());
+        assert!(schedule.workers[0].is_away(date(9)));
+        assert!(!schedule.workers[0].is_away(date(10)));
+        assert!(!schedule.workers[0].is_always_available());
+        assert!(!schedule.workers[1].is_always_available());
+    }
+
+    #[test]
+    fn test_focus_errors() {
+        let tasks = vec![create_task("A", 1, 2, 3, vec![])];
+        let mut schedule = Project::new(tasks, 1, None).unwrap();
+        schedule.workers = vec![Worker::new("Ann")];
+        schedule.focus = Focus {
+            min: 0.55,
+            max: 0.75,
+        };
+        assert!(schedule.validate().is_ok());
+
+        schedule.focus = Focus {
+            min: 0.75,
+            max: 0.55,
+        };
+        assert_eq!(
+            schedule.validate().unwrap_err(),
+            "Focus must be above 0 and at most 1, with min no more than max"
+        );
+        schedule.focus = Focus::FULL;
+        schedule.workers[0].focus = Some(Focus::fixed(0.0));
+        assert_eq!(
+            schedule.validate().unwrap_err(),
+            "Focus of worker Ann must be above 0 and at most 1, with min no more than max"
+        );
+        schedule.workers[0].focus = Some(Focus::fixed(0.6));
+
+        let hours = |h: u64| Duration::from_secs(h * 3600);
+        schedule.interruptions = Some(Interruptions {
+            rate: 2.0,
+            min_time: hours(1),
+            likely_time: hours(2),
+            max_time: hours(1),
+        });
+        assert_eq!(
+            schedule.validate().unwrap_err(),
+            "Invalid interruption duration, expected min <= likely <= max and min < max"
+        );
+        schedule.interruptions = Some(Interruptions {
+            rate: -1.0,
+            min_time: hours(1),
+            likely_time: hours(1),
+            max_time: hours(4),
+        });
+        assert_eq!(
+            schedule.validate().unwrap_err(),
+            "Interruption rate must be 0 or more per workday"
+        );
+    }
 }
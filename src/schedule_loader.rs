@@ -1,88 +1,894 @@
-use crate::{schedule::Project, task::Task};
+use crate::{
+    calendar::{parse_weekdays, Calendar},
+    schedule::{Focus, Interruptions, Milestone, Project, WbsNode, Worker},
+    task::Task,
+    units::{Amount, EstimateUnits, PointsScale, Unit},
+};
 
 use chrono::NaiveDate;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use std::{fs::File, io::Read, path::Path, time::Duration};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
 
 #[derive(Debug, Deserialize, Serialize)]
-struct ScheduleInput {
-    num_workers: usize,
-    start_date: Option<NaiveDate>,
+pub(crate) struct ScheduleInput {
+    // May be left out when `workers` are listed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) num_workers: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) workers: Vec<WorkerInput>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) start_date: Option<NaiveDate>,
+    // Unit of estimates given as plain numbers, days by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) unit: Option<Unit>,
+    // Workdays per story point
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) points: Option<PointsScale>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) focus: Option<FocusInput>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) interruptions: Option<InterruptionsInput>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) include: Vec<IncludeInput>,
+    #[serde(default)]
+    pub(crate) tasks: Vec<TaskInput>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) milestones: Vec<MilestoneInput>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct WorkerInput {
+    pub(crate) name: String,
+    // Percent of each working day spent on the project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) allocation: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) work_days: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) time_off: Vec<TimeOffInput>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) focus: Option<FocusInput>,
+}
+
+// A share of time, e.g. `0.7`, or a range it's drawn from, `{ min: 0.55, max: 0.75 }`
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(untagged)]
+pub(crate) enum FocusInput {
+    Fixed(f64),
+    Range { min: f64, max: f64 },
+}
+
+impl From<FocusInput> for Focus {
+    fn from(input: FocusInput) -> Self {
+        match input {
+            FocusInput::Fixed(focus) => Focus::fixed(focus),
+            FocusInput::Range { min, max } => Focus { min, max },
+        }
+    }
+}
+
+impl From<Focus> for FocusInput {
+    fn from(focus: Focus) -> Self {
+        if focus.min == focus.max {
+            FocusInput::Fixed(focus.min)
+        } else {
+            FocusInput::Range {
+                min: focus.min,
+                max: focus.max,
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct InterruptionsInput {
+    // Average number per workday
+    pub(crate) rate: f64,
+    pub(crate) duration: EstimateInput,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub(crate) enum TimeOffInput {
+    Day(NaiveDate),
+    Range { from: NaiveDate, to: NaiveDate },
+}
+
+impl TryFrom<WorkerInput> for Worker {
+    type Error = String;
+
+    fn try_from(input: WorkerInput) -> Result<Self, Self::Error> {
+        let work_days = match input.work_days {
+            Some(days) => Some(
+                parse_weekdays(&days).map_err(|e| format!("{} for worker {}", e, input.name))?,
+            ),
+            None => None,
+        };
+        Ok(Worker {
+            allocation: input.allocation.map_or(1.0, |percent| percent / 100.0),
+            work_days,
+            time_off: input
+                .time_off
+                .into_iter()
+                .map(|time_off| match time_off {
+                    TimeOffInput::Day(date) => (date, date),
+                    TimeOffInput::Range { from, to } => (from, to),
+                })
+                .collect(),
+            focus: input.focus.map(Focus::from),
+            name: input.name,
+        })
+    }
+}
+
+impl From<&Worker> for WorkerInput {
+    fn from(worker: &Worker) -> Self {
+        WorkerInput {
+            name: worker.name.clone(),
+            allocation: (worker.allocation != 1.0).then_some(worker.allocation * 100.0),
+            work_days: worker
+                .work_days
+                .as_ref()
+                .map(|days| days.iter().map(|day| day.to_string()).collect()),
+            time_off: worker
+                .time_off
+                .iter()
+                .map(|&(from, to)| {
+                    if from == to {
+                        TimeOffInput::Day(from)
+                    } else {
+                        TimeOffInput::Range { from, to }
+                    }
+                })
+                .collect(),
+            focus: worker.focus.map(FocusInput::from),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct MilestoneInput {
+    pub(crate) id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) name: Option<String>,
+    #[serde(default)]
+    pub(crate) dependencies: Vec<String>,
+}
+
+// An included file only contributes tasks (and further includes)
+#[derive(Debug, Deserialize, Serialize)]
+struct IncludedInput {
+    #[serde(default)]
+    include: Vec<IncludeInput>,
+    #[serde(default)]
     tasks: Vec<TaskInput>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
-struct EstimateInput {
-    min: f64,
-    max: f64,
-    likely: f64,
+#[serde(untagged)]
+pub(crate) enum IncludeInput {
+    Path(PathBuf),
+    Namespaced {
+        path: PathBuf,
+        namespace: Option<String>,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct EstimateInput {
+    pub(crate) min: Amount,
+    pub(crate) max: Amount,
+    pub(crate) likely: Amount,
+    // Unit of the values without their own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) unit: Option<Unit>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(crate) struct TaskInput {
+    pub(crate) id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) metadata: BTreeMap<String, serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) estimate: Option<EstimateInput>,
+    #[serde(default)]
+    pub(crate) dependencies: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) not_before: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) fixed_start: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) fixed_finish: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) must_finish_by: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) subtasks: Vec<TaskInput>,
 }
 
+// A CSV row, with `dependencies` separated by `;`
 #[derive(Debug, Deserialize, Serialize)]
-struct TaskInput {
+struct CsvTaskInput {
     id: String,
     name: Option<String>,
-    description: Option<String>,
-    estimate: EstimateInput,
-    dependencies: Vec<String>,
+    min: Amount,
+    likely: Amount,
+    max: Amount,
+    dependencies: Option<String>,
 }
 
-enum FileFormat {
+impl From<CsvTaskInput> for TaskInput {
+    fn from(row: CsvTaskInput) -> Self {
+        TaskInput {
+            id: row.id,
+            name: row.name,
+            description: None,
+            owner: None,
+            tags: Vec::new(),
+            metadata: BTreeMap::new(),
+            estimate: Some(EstimateInput {
+                min: row.min,
+                max: row.max,
+                likely: row.likely,
+                unit: None,
+            }),
+            dependencies: row
+                .dependencies
+                .unwrap_or_default()
+                .split(';')
+                .map(str::trim)
+                .filter(|d| !d.is_empty())
+                .map(String::from)
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
     Yaml,
     Json,
+    Toml,
+    Csv,
+}
+
+impl FromStr for FileFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "yaml" | "yml" => Ok(FileFormat::Yaml),
+            "json" => Ok(FileFormat::Json),
+            "toml" => Ok(FileFormat::Toml),
+            "csv" => Ok(FileFormat::Csv),
+            _ => Err("Unsupported file format. Use .yaml, .yml, .json, .toml or .csv".to_string()),
+        }
+    }
+}
+
+impl FileFormat {
+    pub(crate) fn deserialize<T: DeserializeOwned>(
+        self,
+        contents: &str,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        Ok(match self {
+            FileFormat::Yaml => serde_yaml::from_str(contents)?,
+            FileFormat::Json => serde_json::from_str(contents)?,
+            FileFormat::Toml => {
+                // TOML has native dates, which chrono only accepts as strings
+                let mut value: toml::Value = toml::from_str(contents)?;
+                toml_dates_to_strings(&mut value);
+                value.try_into()?
+            }
+            FileFormat::Csv => return Err("CSV files only contain tasks".into()),
+        })
+    }
+
+    fn parse_schedule(self, contents: &str) -> Result<ScheduleInput, Box<dyn std::error::Error>> {
+        match self {
+            // A CSV file holds only tasks, so the worker count must come from elsewhere
+            FileFormat::Csv => Ok(ScheduleInput {
+                num_workers: Some(1),
+                workers: Vec::new(),
+                start_date: None,
+                unit: None,
+                points: None,
+                focus: None,
+                interruptions: None,
+                include: Vec::new(),
+                tasks: parse_csv_tasks(contents)?,
+                milestones: Vec::new(),
+            }),
+            _ => self.deserialize(contents),
+        }
+    }
+
+    fn parse_included(self, contents: &str) -> Result<IncludedInput, Box<dyn std::error::Error>> {
+        match self {
+            FileFormat::Csv => Ok(IncludedInput {
+                include: Vec::new(),
+                tasks: parse_csv_tasks(contents)?,
+            }),
+            _ => self.deserialize(contents),
+        }
+    }
+}
+
+fn parse_csv_tasks(contents: &str) -> Result<Vec<TaskInput>, Box<dyn std::error::Error>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(contents.as_bytes());
+    let mut tasks = Vec::new();
+    for row in reader.deserialize::<CsvTaskInput>() {
+        tasks.push(row?.into());
+    }
+    Ok(tasks)
+}
+
+fn toml_dates_to_strings(value: &mut toml::Value) {
+    match value {
+        toml::Value::Datetime(date) => *value = toml::Value::String(date.to_string()),
+        toml::Value::Array(values) => values.iter_mut().for_each(toml_dates_to_strings),
+        toml::Value::Table(table) => table.iter_mut().for_each(|(_, v)| toml_dates_to_strings(v)),
+        _ => {}
+    }
+}
+
+struct SourcedTask {
+    input: TaskInput,
+    source: PathBuf,
+}
+
+impl TaskInput {
+    fn collect_ids<'a>(&'a self, ids: &mut HashSet<&'a str>) {
+        ids.insert(&self.id);
+        for subtask in &self.subtasks {
+            subtask.collect_ids(ids);
+        }
+    }
+
+    fn apply_namespace(&mut self, namespace: &str, local_ids: &HashSet<String>) {
+        self.id = format!("{}/{}", namespace, self.id);
+        for dep in &mut self.dependencies {
+            if local_ids.contains(dep) {
+                *dep = format!("{}/{}", namespace, dep);
+            }
+        }
+        for subtask in &mut self.subtasks {
+            subtask.apply_namespace(namespace, local_ids);
+        }
+    }
+}
+
+// Flattens nested `subtasks` into leaf tasks plus the WBS nodes containing them.
+// A parent's dependencies and tags apply to all of its children, and depending
+// on a parent means depending on every leaf task beneath it. Likewise depending
+// on a milestone means depending on the tasks the milestone waits for.
+struct FlattenedTasks {
+    units: EstimateUnits,
+    tasks: Vec<Task>,
+    wbs: Vec<WbsNode>,
+    ids: HashSet<String>,
+    leaves: HashMap<String, Vec<String>>,
+}
+
+impl FlattenedTasks {
+    fn add(
+        &mut self,
+        input: TaskInput,
+        source: &Path,
+        parent: Option<&str>,
+        inherited_dependencies: &[String],
+        inherited_tags: &[String],
+    ) -> Result<Vec<String>, String> {
+        if !self.ids.insert(input.id.clone()) {
+            return Err(format!(
+                "Duplicate task id {} (in {})",
+                input.id,
+                source.display()
+            ));
+        }
+
+        let mut dependencies = inherited_dependencies.to_vec();
+        dependencies.extend(input.dependencies);
+        let mut tags = inherited_tags.to_vec();
+        tags.extend(
+            input
+                .tags
+                .into_iter()
+                .filter(|t| !inherited_tags.contains(t)),
+        );
+
+        if input.subtasks.is_empty() {
+            let estimate = input.estimate.ok_or_else(|| {
+                format!(
+                    "Missing estimate for task {} (in {})",
+                    input.id,
+                    source.display()
+                )
+            })?;
+            let (min_time, likely_time, max_time) = estimate_times(&self.units, &estimate)
+                .map_err(|e| format!("{} for task {} (in {})", e, input.id, source.display()))?;
+            self.tasks.push(Task {
+                id: input.id.clone(),
+                name: input.name,
+                description: input.description,
+                owner: input.owner,
+                tags,
+                metadata: input.metadata,
+                parent: parent.map(String::from),
+                source: Some(source.to_path_buf()),
+                min_time,
+                likely_time,
+                max_time,
+                dependencies,
+                not_before: input.not_before,
+                fixed_start: input.fixed_start,
+                fixed_finish: input.fixed_finish,
+                must_finish_by: input.must_finish_by,
+            });
+            return Ok(vec![input.id]);
+        }
+
+        if input.estimate.is_some() {
+            return Err(format!(
+                "Task {} has subtasks and must not have its own estimate (in {})",
+                input.id,
+                source.display()
+            ));
+        }
+        if input.not_before.is_some()
+            || input.fixed_start.is_some()
+            || input.fixed_finish.is_some()
+            || input.must_finish_by.is_some()
+        {
+            return Err(format!(
+                "Task {} has subtasks and must not have its own date constraints (in {})",
+                input.id,
+                source.display()
+            ));
+        }
+        self.wbs.push(WbsNode {
+            id: input.id.clone(),
+            name: input.name,
+            parent: parent.map(String::from),
+        });
+        let mut leaves = Vec::new();
+        for subtask in input.subtasks {
+            leaves.extend(self.add(subtask, source, Some(&input.id), &dependencies, &tags)?);
+        }
+        self.leaves.insert(input.id, leaves.clone());
+        Ok(leaves)
+    }
+
+    // Milestones may depend on tasks, WBS nodes and earlier milestones
+    fn finish(
+        mut self,
+        milestones: Vec<MilestoneInput>,
+    ) -> (Vec<Task>, Vec<WbsNode>, Vec<Milestone>) {
+        let milestones: Vec<_> = milestones
+            .into_iter()
+            .map(|input| {
+                let dependencies = self.expand(input.dependencies);
+                self.leaves.insert(input.id.clone(), dependencies.clone());
+                Milestone {
+                    id: input.id,
+                    name: input.name,
+                    dependencies,
+                }
+            })
+            .collect();
+        for index in 0..self.tasks.len() {
+            let dependencies = std::mem::take(&mut self.tasks[index].dependencies);
+            self.tasks[index].dependencies = self.expand(dependencies);
+        }
+        (self.tasks, self.wbs, milestones)
+    }
+
+    fn expand(&self, dependencies: Vec<String>) -> Vec<String> {
+        let mut expanded = Vec::new();
+        for dep in dependencies {
+            let leaves = match self.leaves.get(&dep) {
+                Some(leaves) => leaves.clone(),
+                None => vec![dep],
+            };
+            for dep in leaves {
+                if !expanded.contains(&dep) {
+                    expanded.push(dep);
+                }
+            }
+        }
+        expanded
+    }
+}
+
+// Minimum, likely and maximum times of `estimate`
+fn estimate_times(
+    units: &EstimateUnits,
+    estimate: &EstimateInput,
+) -> Result<(Duration, Duration, Duration), String> {
+    let time = |amount, per_point: fn(&PointsScale) -> f64| {
+        units
+            .days(amount, estimate.unit, per_point)
+            .map(|days| Duration::from_secs_f64(days * 24.0 * 60.0 * 60.0))
+    };
+    Ok((
+        time(estimate.min, |scale| scale.min)?,
+        time(estimate.likely, |scale| scale.likely)?,
+        time(estimate.max, |scale| scale.max)?,
+    ))
+}
+
+impl From<&Task> for EstimateInput {
+    fn from(task: &Task) -> Self {
+        EstimateInput::from_times(task.min_time, task.likely_time, task.max_time)
+    }
+}
+
+impl EstimateInput {
+    fn from_times(min: Duration, likely: Duration, max: Duration) -> Self {
+        let days = |d: Duration| d.as_secs_f64() / (24.0 * 60.0 * 60.0);
+        EstimateInput {
+            min: days(min).into(),
+            max: days(max).into(),
+            likely: days(likely).into(),
+            unit: None,
+        }
+    }
+}
+
+// Rebuilds the nested input for a project, placing each WBS node where its
+// first task appears. Dependencies were expanded onto the leaf tasks when
+// loading, so they are all written there.
+impl From<&Project> for ScheduleInput {
+    fn from(project: &Project) -> Self {
+        fn children(project: &Project, parent: Option<&str>) -> Vec<TaskInput> {
+            let mut items: Vec<(usize, TaskInput)> = project
+                .tasks
+                .iter()
+                .enumerate()
+                .filter(|(_, task)| task.parent.as_deref() == parent)
+                .map(|(index, task)| {
+                    let input = TaskInput {
+                        id: task.id.clone(),
+                        name: task.name.clone(),
+                        description: task.description.clone(),
+                        owner: task.owner.clone(),
+                        tags: task.tags.clone(),
+                        metadata: task.metadata.clone(),
+                        estimate: Some(EstimateInput::from(task)),
+                        dependencies: task.dependencies.clone(),
+                        not_before: task.not_before,
+                        fixed_start: task.fixed_start,
+                        fixed_finish: task.fixed_finish,
+                        must_finish_by: task.must_finish_by,
+                        subtasks: Vec::new(),
+                    };
+                    (index, input)
+                })
+                .collect();
+            for node in project.wbs.iter().filter(|n| n.parent.as_deref() == parent) {
+                let first_task = project
+                    .tasks
+                    .iter()
+                    .position(|t| {
+                        project
+                            .wbs_ancestors(t.parent.as_deref())
+                            .contains(&node.id.as_str())
+                    })
+                    .unwrap_or(usize::MAX);
+                let input = TaskInput {
+                    id: node.id.clone(),
+                    name: node.name.clone(),
+                    subtasks: children(project, Some(&node.id)),
+                    ..Default::default()
+                };
+                items.push((first_task, input));
+            }
+            items.sort_by_key(|(index, _)| *index);
+            items.into_iter().map(|(_, input)| input).collect()
+        }
+
+        ScheduleInput {
+            num_workers: Some(project.num_workers),
+            workers: project.workers.iter().map(WorkerInput::from).collect(),
+            start_date: project.start_date,
+            unit: None,
+            points: None,
+            focus: (project.focus != Focus::FULL).then_some(project.focus.into()),
+            interruptions: project
+                .interruptions
+                .as_ref()
+                .map(|interruptions| InterruptionsInput {
+                    rate: interruptions.rate,
+                    duration: EstimateInput::from_times(
+                        interruptions.min_time,
+                        interruptions.likely_time,
+                        interruptions.max_time,
+                    ),
+                }),
+            include: Vec::new(),
+            tasks: children(project, None),
+            milestones: project
+                .milestones
+                .iter()
+                .map(|m| MilestoneInput {
+                    id: m.id.clone(),
+                    name: m.name.clone(),
+                    dependencies: m.dependencies.clone(),
+                })
+                .collect(),
+        }
+    }
 }
 
 impl Project {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut file = File::open(&path)?;
+        Self::from_file_with_format(path, None, Calendar::new())
+    }
+
+    // Like `from_file`, but `format` overrides detection from the file
+    // extension, and estimates in hours and weeks are read against `calendar`
+    pub fn from_file_with_format<P: AsRef<Path>>(
+        path: P,
+        format: Option<FileFormat>,
+        calendar: Calendar,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let format = match format {
+            Some(format) => format,
+            None => Self::detect_format(path)?,
+        };
+        let contents = Self::read_contents(path)?;
+        Self::from_contents(
+            &contents,
+            format,
+            path,
+            vec![path.canonicalize()?],
+            calendar,
+        )
+    }
+
+    // Reads a project from e.g. stdin, resolving includes against the current directory
+    pub fn from_reader<R: Read>(
+        mut reader: R,
+        format: FileFormat,
+        calendar: Calendar,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
+        reader.read_to_string(&mut contents)?;
+        Self::from_contents(
+            &contents,
+            format,
+            Path::new("<stdin>"),
+            Vec::new(),
+            calendar,
+        )
+    }
 
-        let format = Self::detect_format(&path)?;
-        let input: ScheduleInput = match format {
-            FileFormat::Yaml => serde_yaml::from_str(&contents)?,
-            FileFormat::Json => serde_json::from_str(&contents)?,
+    fn from_contents(
+        contents: &str,
+        format: FileFormat,
+        path: &Path,
+        includes: Vec<PathBuf>,
+        calendar: Calendar,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let input = format.parse_schedule(contents)?;
+        Self::from_input(input, path, includes, calendar)
+    }
+
+    // Builds a project from parsed input, shared with the importers
+    pub(crate) fn from_input(
+        input: ScheduleInput,
+        path: &Path,
+        mut includes: Vec<PathBuf>,
+        calendar: Calendar,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let sourced = Self::collect_tasks(path, input.include, input.tasks, &mut includes)?;
+
+        if let Some(scale) = &input.points {
+            if !(0.0 < scale.min && scale.min <= scale.likely && scale.likely <= scale.max) {
+                return Err(
+                    "Invalid points days per point, expected 0 < min <= likely <= max".into(),
+                );
+            }
+        }
+        let mut units = EstimateUnits::new(&calendar);
+        units.unit = input.unit.unwrap_or(Unit::Days);
+        units.points = input.points;
+        let mut flattened = FlattenedTasks {
+            units,
+            tasks: Vec::new(),
+            wbs: Vec::new(),
+            ids: HashSet::new(),
+            leaves: HashMap::new(),
         };
+        for task in sourced {
+            flattened.add(task.input, &task.source, None, &[], &[])?;
+        }
+        let interruptions = match input.interruptions {
+            Some(interruptions) => {
+                let (min_time, likely_time, max_time) =
+                    estimate_times(&flattened.units, &interruptions.duration)
+                        .map_err(|e| format!("{} for interruptions", e))?;
+                Some(Interruptions {
+                    rate: interruptions.rate,
+                    min_time,
+                    likely_time,
+                    max_time,
+                })
+            }
+            None => None,
+        };
+        let (tasks, wbs, milestones) = flattened.finish(input.milestones);
 
-        let tasks = input
-            .tasks
+        let workers = input
+            .workers
             .into_iter()
-            .map(|t| Task {
-                id: t.id,
-                min_time: Duration::from_secs_f64(t.estimate.min * 24.0 * 60.0 * 60.0),
-                likely_time: Duration::from_secs_f64(t.estimate.likely * 24.0 * 60.0 * 60.0),
-                max_time: Duration::from_secs_f64(t.estimate.max * 24.0 * 60.0 * 60.0),
-                dependencies: t.dependencies,
-            })
-            .collect();
+            .map(Worker::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let num_workers = match (input.num_workers, workers.len()) {
+            (Some(num_workers), _) => num_workers,
+            (None, 0) => return Err("Missing num_workers or workers".into()),
+            (None, listed) => listed,
+        };
 
-        let schedule = Project::new(tasks, input.num_workers, input.start_date)?;
+        let schedule = Project {
+            tasks,
+            num_workers,
+            start_date: input.start_date,
+            wbs,
+            milestones,
+            workers,
+            calendar,
+            focus: input.focus.map_or(Focus::FULL, Focus::from),
+            interruptions,
+        };
 
         schedule.validate()?;
 
         Ok(schedule)
     }
 
-    fn detect_format<P: AsRef<Path>>(path: P) -> Result<FileFormat, Box<dyn std::error::Error>> {
+    pub fn to_string_with_format(
+        &self,
+        format: FileFormat,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let input = ScheduleInput::from(self);
+        Ok(match format {
+            FileFormat::Yaml => serde_yaml::to_string(&input)?,
+            FileFormat::Json => serde_json::to_string_pretty(&input)?,
+            FileFormat::Toml => toml::to_string(&input)?,
+            FileFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(Vec::new());
+                for task in &self.tasks {
+                    let estimate = EstimateInput::from(task);
+                    writer.serialize(CsvTaskInput {
+                        id: task.id.clone(),
+                        name: task.name.clone(),
+                        min: estimate.min,
+                        likely: estimate.likely,
+                        max: estimate.max,
+                        dependencies: Some(task.dependencies.join(";")),
+                    })?;
+                }
+                String::from_utf8(writer.into_inner()?)?
+            }
+        })
+    }
+
+    // Writes the project in the format given by the file extension
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let format = Self::detect_format(&path)?;
+        std::fs::write(path, self.to_string_with_format(format)?)?;
+        Ok(())
+    }
+
+    fn read_contents(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+        let mut file = File::open(path)?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        Ok(contents)
+    }
+
+    // Gathers a file's tasks followed by those of its includes (recursively).
+    // Includes are resolved relative to the including file, and a namespace
+    // prefixes every id defined under the include along with any dependency
+    // on those ids. `includes` holds the chain of files being loaded.
+    fn collect_tasks(
+        path: &Path,
+        include: Vec<IncludeInput>,
+        tasks: Vec<TaskInput>,
+        includes: &mut Vec<PathBuf>,
+    ) -> Result<Vec<SourcedTask>, Box<dyn std::error::Error>> {
+        let mut collected: Vec<_> = tasks
+            .into_iter()
+            .map(|input| SourcedTask {
+                input,
+                source: path.to_path_buf(),
+            })
+            .collect();
+
+        let base = path.parent().unwrap_or(Path::new(""));
+        for entry in include {
+            let (include_path, namespace) = match entry {
+                IncludeInput::Path(path) => (base.join(path), None),
+                IncludeInput::Namespaced { path, namespace } => (base.join(path), namespace),
+            };
+            let canonical = include_path.canonicalize().map_err(|e| {
+                format!(
+                    "Unable to include {} from {}: {}",
+                    include_path.display(),
+                    path.display(),
+                    e
+                )
+            })?;
+            if includes.contains(&canonical) {
+                return Err(format!(
+                    "Include cycle detected: {} includes {}",
+                    path.display(),
+                    include_path.display()
+                )
+                .into());
+            }
+
+            let input = Self::detect_format(&include_path)
+                .and_then(|format| format.parse_included(&Self::read_contents(&include_path)?))
+                .map_err(|e| format!("{}: {}", include_path.display(), e))?;
+            includes.push(canonical);
+            let mut included =
+                Self::collect_tasks(&include_path, input.include, input.tasks, includes)?;
+            includes.pop();
+
+            if let Some(namespace) = namespace {
+                let mut local_ids = HashSet::new();
+                for task in &included {
+                    task.input.collect_ids(&mut local_ids);
+                }
+                let local_ids: HashSet<String> = local_ids.into_iter().map(String::from).collect();
+                for task in &mut included {
+                    task.input.apply_namespace(&namespace, &local_ids);
+                }
+            }
+            collected.extend(included);
+        }
+
+        Ok(collected)
+    }
+
+    pub(crate) fn detect_format<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<FileFormat, Box<dyn std::error::Error>> {
         let extension = path
             .as_ref()
             .extension()
             .and_then(std::ffi::OsStr::to_str)
-            .ok_or("File has no extension")?
-            .to_lowercase();
+            .ok_or("File has no extension")?;
 
-        match extension.as_str() {
-            "yaml" | "yml" => Ok(FileFormat::Yaml),
-            "json" => Ok(FileFormat::Json),
-            _ => Err("Unsupported file format. Use .yaml, .yml, or .json".into()),
-        }
+        Ok(extension.parse()?)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Weekday;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -185,6 +991,669 @@ tasks:
             .contains("Minimum duration greater than maximum for task A"));
     }
 
+    #[test]
+    fn test_load_descriptive_fields_yaml() {
+        let yaml_content = r#"
+num_workers: 1
+tasks:
+  - id: T-417
+    name: Implement OAuth flow
+    description: Support login via the corporate identity provider
+    owner: alice
+    tags: [backend, phase-2]
+    metadata:
+      jira: AUTH-12
+      story_points: 5
+    estimate:
+        min: 1
+        likely: 2
+        max: 3
+    dependencies: []
+  - id: T-418
+    estimate:
+        min: 1
+        likely: 2
+        max: 3
+    dependencies: [T-417]
+"#;
+        let (_temp_file, path) = create_temp_file(yaml_content, "yaml");
+        let schedule = Project::from_file(path).unwrap();
+
+        let task = &schedule.tasks[0];
+        assert_eq!(task.display_name(), "Implement OAuth flow");
+        assert_eq!(
+            task.description.as_deref(),
+            Some("Support login via the corporate identity provider")
+        );
+        assert_eq!(task.owner.as_deref(), Some("alice"));
+        assert_eq!(task.tags, vec!["backend", "phase-2"]);
+        assert_eq!(task.metadata["jira"], "AUTH-12");
+        assert_eq!(task.metadata["story_points"], 5);
+
+        let task = &schedule.tasks[1];
+        assert_eq!(task.display_name(), "T-418");
+        assert!(task.owner.is_none());
+        assert!(task.tags.is_empty());
+        assert!(task.metadata.is_empty());
+    }
+
+    #[test]
+    fn test_load_subtasks_yaml() {
+        let yaml_content = r#"
+num_workers: 2
+tasks:
+  - id: Design
+    estimate: { min: 1, likely: 2, max: 3 }
+  - id: Epic
+    name: Checkout
+    dependencies: [Design]
+    tags: [frontend]
+    subtasks:
+      - id: Story
+        subtasks:
+          - id: Cart
+            estimate: { min: 1, likely: 2, max: 3 }
+          - id: Payment
+            estimate: { min: 1, likely: 2, max: 3 }
+            dependencies: [Cart]
+      - id: Receipt
+        estimate: { min: 1, likely: 2, max: 3 }
+        dependencies: [Story]
+  - id: Launch
+    estimate: { min: 1, likely: 2, max: 3 }
+    dependencies: [Epic]
+"#;
+        let (_temp_file, path) = create_temp_file(yaml_content, "yaml");
+        let schedule = Project::from_file(path).unwrap();
+
+        let ids: Vec<_> = schedule.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["Design", "Cart", "Payment", "Receipt", "Launch"]);
+        let wbs: Vec<_> = schedule.wbs.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(wbs, vec!["Epic", "Story"]);
+        assert_eq!(schedule.wbs[0].display_name(), "Checkout");
+        assert_eq!(schedule.wbs[1].parent.as_deref(), Some("Epic"));
+
+        let task = |id: &str| schedule.tasks.iter().find(|t| t.id == id).unwrap();
+        assert_eq!(task("Cart").parent.as_deref(), Some("Story"));
+        assert_eq!(task("Cart").dependencies, vec!["Design"]);
+        assert_eq!(task("Cart").tags, vec!["frontend"]);
+        assert_eq!(task("Payment").dependencies, vec!["Design", "Cart"]);
+        assert_eq!(task("Receipt").parent.as_deref(), Some("Epic"));
+        assert_eq!(
+            task("Receipt").dependencies,
+            vec!["Design", "Cart", "Payment"]
+        );
+        assert_eq!(
+            task("Launch").dependencies,
+            vec!["Cart", "Payment", "Receipt"]
+        );
+    }
+
+    #[test]
+    fn test_load_milestones_yaml() {
+        let yaml_content = r#"
+num_workers: 2
+tasks:
+  - id: Epic
+    subtasks:
+      - id: Cart
+        estimate: { min: 1, likely: 2, max: 3 }
+      - id: Payment
+        estimate: { min: 1, likely: 2, max: 3 }
+  - id: Docs
+    estimate: { min: 1, likely: 2, max: 3 }
+  - id: Launch
+    estimate: { min: 1, likely: 2, max: 3 }
+    dependencies: [Release]
+milestones:
+  - id: Beta
+    name: Public beta
+    dependencies: [Epic]
+  - id: Release
+    dependencies: [Beta, Docs]
+"#;
+        let (_temp_file, path) = create_temp_file(yaml_content, "yaml");
+        let schedule = Project::from_file(path).unwrap();
+
+        assert_eq!(
+            schedule.milestones,
+            vec![
+                Milestone {
+                    id: "Beta".to_string(),
+                    name: Some("Public beta".to_string()),
+                    dependencies: vec!["Cart".to_string(), "Payment".to_string()],
+                },
+                Milestone {
+                    id: "Release".to_string(),
+                    name: None,
+                    dependencies: vec![
+                        "Cart".to_string(),
+                        "Payment".to_string(),
+                        "Docs".to_string()
+                    ],
+                },
+            ]
+        );
+        assert_eq!(
+            schedule.tasks[3].dependencies,
+            vec!["Cart", "Payment", "Docs"]
+        );
+    }
+
+    #[test]
+    fn test_load_subtasks_errors() {
+        let cases = [
+            (
+                "  - id: A\n    estimate: { min: 1, likely: 2, max: 3 }\n    subtasks:\n      - id: B\n        estimate: { min: 1, likely: 2, max: 3 }\n",
+                "Task A has subtasks and must not have its own estimate",
+            ),
+            ("  - id: A\n    dependencies: []\n", "Missing estimate for task A"),
+            (
+                "  - id: A\n    subtasks:\n      - id: A\n        estimate: { min: 1, likely: 2, max: 3 }\n",
+                "Duplicate task id A",
+            ),
+            (
+                "  - id: A\n    subtasks:\n      - id: B\n        estimate: { min: 1, likely: 2, max: 3 }\n        dependencies: [A]\n",
+                "Cyclic dependency detected",
+            ),
+        ];
+        for (tasks, expected) in cases {
+            let yaml_content = format!("num_workers: 1\ntasks:\n{}", tasks);
+            let (_temp_file, path) = create_temp_file(&yaml_content, "yaml");
+            let result = Project::from_file(path);
+            assert!(
+                result.as_ref().unwrap_err().to_string().contains(expected),
+                "Expected error '{}', got {:?}",
+                expected,
+                result
+            );
+        }
+    }
+
+    fn write_files(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, content) in files {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_load_with_includes() {
+        let dir = write_files(&[
+            (
+                "project.yaml",
+                r#"
+num_workers: 2
+include:
+  - common.yaml
+  - path: backend/tasks.json
+    namespace: backend
+tasks:
+  - id: Launch
+    estimate: { min: 1, likely: 2, max: 3 }
+    dependencies: [backend/Api, Kickoff]
+"#,
+            ),
+            (
+                "common.yaml",
+                r#"
+tasks:
+  - id: Kickoff
+    estimate: { min: 1, likely: 2, max: 3 }
+"#,
+            ),
+            (
+                "backend/tasks.json",
+                r#"
+{
+  "include": [{ "path": "db.yaml", "namespace": "db" }],
+  "tasks": [
+    {
+      "id": "Auth",
+      "estimate": { "min": 1, "likely": 2, "max": 3 },
+      "dependencies": ["Kickoff"]
+    },
+    {
+      "id": "Api",
+      "estimate": { "min": 1, "likely": 2, "max": 3 },
+      "dependencies": ["Auth", "db/Schema"]
+    }
+  ]
+}
+"#,
+            ),
+            (
+                "backend/db.yaml",
+                r#"
+tasks:
+  - id: Schema
+    estimate: { min: 1, likely: 2, max: 3 }
+"#,
+            ),
+        ]);
+        let schedule = Project::from_file(dir.path().join("project.yaml")).unwrap();
+
+        let ids: Vec<_> = schedule.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "Launch",
+                "Kickoff",
+                "backend/Auth",
+                "backend/Api",
+                "backend/db/Schema"
+            ]
+        );
+        let task = |id: &str| schedule.tasks.iter().find(|t| t.id == id).unwrap();
+        assert_eq!(task("backend/Auth").dependencies, vec!["Kickoff"]);
+        assert_eq!(
+            task("backend/Api").dependencies,
+            vec!["backend/Auth", "backend/db/Schema"]
+        );
+        assert_eq!(
+            task("backend/db/Schema").source,
+            Some(dir.path().join("backend").join("db.yaml"))
+        );
+    }
+
+    #[test]
+    fn test_include_errors_name_source_file() {
+        let dir = write_files(&[
+            (
+                "project.yaml",
+                "num_workers: 1\ninclude: [backend.yaml]\ntasks: []\n",
+            ),
+            (
+                "backend.yaml",
+                "tasks:\n  - id: Auth\n    estimate: { min: 1, likely: 2, max: 3 }\n    dependencies: [Kickoff]\n",
+            ),
+        ]);
+        let err = Project::from_file(dir.path().join("project.yaml"))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.starts_with("Missing dependency Kickoff for task Auth (in ")
+                && err.ends_with("backend.yaml)"),
+            "Unexpected error message: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_include_cycle() {
+        let dir = write_files(&[
+            ("project.yaml", "num_workers: 1\ninclude: [a.yaml]\n"),
+            ("a.yaml", "include: [b.yaml]\n"),
+            ("b.yaml", "include: [a.yaml]\n"),
+        ]);
+        let err = Project::from_file(dir.path().join("project.yaml"))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.starts_with("Include cycle detected"),
+            "Unexpected error message: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_missing_include() {
+        let dir = write_files(&[("project.yaml", "num_workers: 1\ninclude: [nope.yaml]\n")]);
+        let err = Project::from_file(dir.path().join("project.yaml"))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.starts_with("Unable to include"),
+            "Unexpected error message: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_load_from_toml() {
+        let toml_content = r#"
+num_workers = 3
+start_date = 2024-08-01
+
+[[tasks]]
+id = "A"
+name = "Design"
+estimate = { min = 1, likely = 2.4, max = 3 }
+
+[[tasks]]
+id = "B"
+estimate = { min = 2, likely = 2.4, max = 4 }
+dependencies = ["A"]
+"#;
+        let (_temp_file, path) = create_temp_file(toml_content, "toml");
+        let schedule = Project::from_file(path).unwrap();
+        assert_eq!(schedule.tasks.len(), 2);
+        assert_eq!(schedule.num_workers, 3);
+        assert_eq!(schedule.start_date, NaiveDate::from_ymd_opt(2024, 8, 1));
+        assert_eq!(schedule.tasks[0].display_name(), "Design");
+        assert_eq!(schedule.tasks[1].dependencies, vec!["A"]);
+    }
+
+    #[test]
+    fn test_load_from_csv() {
+        let csv_content = "\
+id,name,min,likely,max,dependencies
+A,Design,1,2,3,
+B,,2,3,4,A
+C,Build everything,1.5,2,3.5,A; B
+";
+        let (_temp_file, path) = create_temp_file(csv_content, "csv");
+        let schedule = Project::from_file(path).unwrap();
+        assert_eq!(schedule.tasks.len(), 3);
+        assert_eq!(schedule.num_workers, 1);
+        assert_eq!(schedule.tasks[0].display_name(), "Design");
+        assert!(schedule.tasks[0].dependencies.is_empty());
+        assert_eq!(schedule.tasks[1].display_name(), "B");
+        assert_eq!(schedule.tasks[2].dependencies, vec!["A", "B"]);
+        assert_eq!(
+            schedule.tasks[2].min_time.as_secs_f64(),
+            1.5 * 24.0 * 60.0 * 60.0
+        );
+    }
+
+    #[test]
+    fn test_include_csv() {
+        let dir = write_files(&[
+            (
+                "project.toml",
+                "num_workers = 2\ninclude = [{ path = \"estimates.csv\", namespace = \"pm\" }]\n",
+            ),
+            (
+                "estimates.csv",
+                "id,name,min,likely,max,dependencies\nA,,1,2,3,\nB,,1,2,3,A\n",
+            ),
+        ]);
+        let schedule = Project::from_file(dir.path().join("project.toml")).unwrap();
+        assert_eq!(schedule.num_workers, 2);
+        assert_eq!(schedule.tasks[1].id, "pm/B");
+        assert_eq!(schedule.tasks[1].dependencies, vec!["pm/A"]);
+    }
+
+    #[test]
+    fn test_format_override() {
+        let yaml_content =
+            "num_workers: 2\ntasks:\n  - id: A\n    estimate: { min: 1, likely: 2, max: 3 }\n";
+        let (_temp_file, path) = create_temp_file(yaml_content, "txt");
+        assert!(Project::from_file(&path).is_err());
+        let schedule =
+            Project::from_file_with_format(&path, Some(FileFormat::Yaml), Calendar::new()).unwrap();
+        assert_eq!(schedule.tasks.len(), 1);
+    }
+
+    #[test]
+    fn test_load_from_reader() {
+        let json_content = r#"{ "num_workers": 2, "tasks": [{ "id": "A", "estimate": { "min": 1, "likely": 2, "max": 3 } }] }"#;
+        let schedule =
+            Project::from_reader(json_content.as_bytes(), FileFormat::Json, Calendar::new())
+                .unwrap();
+        assert_eq!(schedule.num_workers, 2);
+        assert_eq!(schedule.tasks.len(), 1);
+    }
+
+    #[test]
+    fn test_load_workers_yaml() {
+        let yaml_content = r#"
+workers:
+  - name: alice
+  - name: bob
+    allocation: 50
+    work_days: [Mon, Tue, Wed]
+    time_off:
+      - 2024-08-05
+      - { from: 2024-08-12, to: 2024-08-16 }
+tasks:
+  - id: A
+    owner: bob
+    estimate: { min: 1, likely: 2, max: 3 }
+"#;
+        let (_temp_file, path) = create_temp_file(yaml_content, "yaml");
+        let schedule = Project::from_file(path).unwrap();
+
+        assert_eq!(schedule.num_workers, 2);
+        assert_eq!(schedule.workers[0], Worker::new("alice"));
+        let bob = &schedule.workers[1];
+        assert_eq!(bob.allocation, 0.5);
+        assert_eq!(
+            bob.work_days,
+            Some(vec![Weekday::Mon, Weekday::Tue, Weekday::Wed])
+        );
+        let date = |d| NaiveDate::from_ymd_opt(2024, 8, d).unwrap();
+        assert_eq!(bob.time_off, vec![(date(5), date(5)), (date(12), date(16))]);
+
+        for format in [FileFormat::Yaml, FileFormat::Json, FileFormat::Toml] {
+            let written = schedule.to_string_with_format(format).unwrap();
+            let loaded = Project::from_reader(written.as_bytes(), format, Calendar::new()).unwrap();
+            assert_eq!(loaded.workers, schedule.workers);
+        }
+
+        let yaml_content = "workers:\n  - name: bob\n    work_days: [Mon, Funday]\ntasks: []\n";
+        let (_temp_file, path) = create_temp_file(yaml_content, "yaml");
+        let error = Project::from_file(path).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown day of the week 'Funday' for worker bob"
+        );
+
+        let (_temp_file, path) = create_temp_file("tasks: []\n", "yaml");
+        let error = Project::from_file(path).unwrap_err();
+        assert_eq!(error.to_string(), "Missing num_workers or workers");
+    }
+
+    #[test]
+    fn test_load_focus_and_interruptions_yaml() {
+        let yaml_content = r#"
+focus: { min: 0.55, max: 0.75 }
+interruptions:
+  rate: 1.5
+  duration: { min: 1h, likely: 2h, max: 1d }
+workers:
+  - name: alice
+  - name: bob
+    focus: 0.5
+tasks:
+  - id: A
+    estimate: { min: 1, likely: 2, max: 3 }
+"#;
+        let schedule =
+            Project::from_reader(yaml_content.as_bytes(), FileFormat::Yaml, Calendar::new())
+                .unwrap();
+
+        assert_eq!(
+            schedule.focus,
+            Focus {
+                min: 0.55,
+                max: 0.75
+            }
+        );
+        assert_eq!(schedule.workers[0].focus, None);
+        assert_eq!(schedule.workers[1].focus, Some(Focus::fixed(0.5)));
+        let hours = |h: u64| Duration::from_secs(h * 86400 / 8);
+        assert_eq!(
+            schedule.interruptions,
+            Some(Interruptions {
+                rate: 1.5,
+                min_time: hours(1),
+                likely_time: hours(2),
+                max_time: hours(8),
+            })
+        );
+
+        for format in [FileFormat::Yaml, FileFormat::Json, FileFormat::Toml] {
+            let written = schedule.to_string_with_format(format).unwrap();
+            let loaded = Project::from_reader(written.as_bytes(), format, Calendar::new()).unwrap();
+            assert_eq!(loaded.focus, schedule.focus);
+            assert_eq!(loaded.workers, schedule.workers);
+            assert_eq!(loaded.interruptions, schedule.interruptions);
+        }
+    }
+
+    #[test]
+    fn test_load_date_constraints_yaml() {
+        let yaml_content = r#"
+num_workers: 1
+tasks:
+  - id: A
+    not_before: 2026-11-02
+    must_finish_by: 2026-11-20
+    estimate: { min: 1, likely: 2, max: 3 }
+  - id: B
+    fixed_finish: 2026-11-13
+    estimate: { min: 1, likely: 2, max: 3 }
+"#;
+        let (_temp_file, path) = create_temp_file(yaml_content, "yaml");
+        let schedule = Project::from_file(path).unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2026, 11, d);
+
+        assert_eq!(schedule.tasks[0].not_before, date(2));
+        assert_eq!(schedule.tasks[0].must_finish_by, date(20));
+        assert_eq!(schedule.tasks[0].fixed_start, None);
+        assert_eq!(schedule.tasks[1].fixed_finish, date(13));
+
+        for format in [FileFormat::Yaml, FileFormat::Json, FileFormat::Toml] {
+            let written = schedule.to_string_with_format(format).unwrap();
+            let loaded = Project::from_reader(written.as_bytes(), format, Calendar::new()).unwrap();
+            for (a, b) in loaded.tasks.iter().zip(&schedule.tasks) {
+                assert_eq!(a.not_before, b.not_before);
+                assert_eq!(a.fixed_start, b.fixed_start);
+                assert_eq!(a.fixed_finish, b.fixed_finish);
+                assert_eq!(a.must_finish_by, b.must_finish_by);
+            }
+        }
+
+        let yaml_content = "num_workers: 1\ntasks:\n  - id: A\n    not_before: 2026-11-02\n    subtasks:\n      - id: B\n        estimate: { min: 1, likely: 2, max: 3 }\n";
+        let (_temp_file, path) = create_temp_file(yaml_content, "yaml");
+        let error = Project::from_file(&path).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Task A has subtasks and must not have its own date constraints (in {})",
+                path.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_load_estimate_units_yaml() {
+        let yaml_content = r#"
+num_workers: 1
+unit: hours
+points: { min: 0.5, likely: 1, max: 2 }
+tasks:
+  - id: A
+    estimate: { min: 4, likely: 1d, max: 1.5w }
+  - id: B
+    estimate: { min: 2, likely: 3, max: 5, unit: points }
+"#;
+        let mut calendar = Calendar::new();
+        calendar.set_work_days(&[Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu]);
+        let schedule =
+            Project::from_reader(yaml_content.as_bytes(), FileFormat::Yaml, calendar).unwrap();
+        let days = |d: Duration| d.as_secs_f64() / 86400.0;
+
+        let a = &schedule.tasks[0];
+        assert_eq!(
+            (days(a.min_time), days(a.likely_time), days(a.max_time)),
+            (0.5, 1.0, 6.0)
+        );
+        let b = &schedule.tasks[1];
+        assert_eq!(
+            (days(b.min_time), days(b.likely_time), days(b.max_time)),
+            (1.0, 3.0, 10.0)
+        );
+
+        let csv = "id,name,min,likely,max,dependencies\nA,,4h,1,2,\n";
+        let schedule =
+            Project::from_reader(csv.as_bytes(), FileFormat::Csv, Calendar::new()).unwrap();
+        assert_eq!(days(schedule.tasks[0].min_time), 0.5);
+
+        let yaml_content =
+            "num_workers: 1\ntasks:\n  - id: A\n    estimate: { min: 1pt, likely: 2, max: 3 }\n";
+        let error =
+            Project::from_reader(yaml_content.as_bytes(), FileFormat::Yaml, Calendar::new())
+                .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Estimates in points need the project's `points` days per point for task A (in <stdin>)"
+        );
+        let yaml_content =
+            "num_workers: 1\ntasks:\n  - id: A\n    estimate: { min: 1y, likely: 2, max: 3 }\n";
+        assert!(
+            Project::from_reader(yaml_content.as_bytes(), FileFormat::Yaml, Calendar::new())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_round_trip_all_formats() {
+        let yaml_content = r#"
+num_workers: 2
+start_date: 2024-08-01
+tasks:
+  - id: Design
+    name: Design the thing
+    owner: alice
+    tags: [ux]
+    metadata: { jira: UX-1 }
+    estimate: { min: 1, likely: 2, max: 3 }
+  - id: Epic
+    dependencies: [Design]
+    subtasks:
+      - id: Cart
+        estimate: { min: 1, likely: 2, max: 3 }
+      - id: Payment
+        estimate: { min: 1.5, likely: 2, max: 3.5 }
+        dependencies: [Cart]
+  - id: Launch
+    estimate: { min: 1, likely: 2, max: 3 }
+    dependencies: [Epic]
+"#;
+        let (_temp_file, path) = create_temp_file(yaml_content, "yaml");
+        let original = Project::from_file(path).unwrap();
+
+        for format in [FileFormat::Yaml, FileFormat::Json, FileFormat::Toml] {
+            let written = original.to_string_with_format(format).unwrap();
+            let loaded = Project::from_reader(written.as_bytes(), format, Calendar::new()).unwrap();
+
+            assert_eq!(loaded.num_workers, 2);
+            assert_eq!(loaded.start_date, original.start_date);
+            assert_eq!(loaded.wbs, original.wbs);
+            for (a, b) in loaded.tasks.iter().zip(&original.tasks) {
+                assert_eq!(a.id, b.id);
+                assert_eq!(a.name, b.name);
+                assert_eq!(a.owner, b.owner);
+                assert_eq!(a.tags, b.tags);
+                assert_eq!(a.metadata, b.metadata);
+                assert_eq!(a.parent, b.parent);
+                assert_eq!(a.dependencies, b.dependencies);
+                assert_eq!(a.min_time, b.min_time);
+                assert_eq!(a.max_time, b.max_time);
+            }
+        }
+
+        let written = original.to_string_with_format(FileFormat::Csv).unwrap();
+        assert!(written.starts_with("id,name,min,likely,max,dependencies\n"));
+        let loaded =
+            Project::from_reader(written.as_bytes(), FileFormat::Csv, Calendar::new()).unwrap();
+        assert_eq!(loaded.tasks.len(), original.tasks.len());
+        assert_eq!(loaded.tasks[3].dependencies, vec!["Cart", "Payment"]);
+    }
+
+    #[test]
+    fn test_parse_file_format() {
+        assert_eq!("YML".parse::<FileFormat>(), Ok(FileFormat::Yaml));
+        assert_eq!("toml".parse::<FileFormat>(), Ok(FileFormat::Toml));
+        assert_eq!("csv".parse::<FileFormat>(), Ok(FileFormat::Csv));
+        assert!("xlsx".parse::<FileFormat>().is_err());
+    }
+
     #[test]
     fn test_load_with_floating_point_times_yaml() {
         let yaml_content = r#"
@@ -1,11 +1,20 @@
-use crate::schedule::Project;
+use crate::{
+    schedule::{Focus, Project},
+    timeline::Timeline,
+};
 
 use distimate::Pert;
 use rand::prelude::*;
 use rayon::prelude::*;
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::HashMap,
+    fmt,
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 
@@ -13,121 +22,628 @@ use std::{
 pub struct SimulationResult {
     pub total_project_duration: Duration,
     pub total_effort_time: Duration,
+    // Indexed like `Project::tasks`
+    pub task_start_times: Vec<Duration>,
+    pub task_finish_times: Vec<Duration>,
+    pub task_effort_times: Vec<Duration>,
+}
+
+#[derive(Clone, Default)]
+pub struct SimulationOptions {
+    // Makes results reproducible. Each iteration draws from its own generator,
+    // seeded from this and the iteration number.
+    pub seed: Option<u64>,
+    // Runs the simulation in its own pool of this many threads instead of
+    // rayon's global pool. Seeded results are the same for any number.
+    pub threads: Option<usize>,
+    // Called from the worker threads after each iteration, with the number of
+    // iterations finished so far
+    pub progress: Option<ProgressCallback>,
+    // Once cancelled, iterations that haven't started are skipped and only the
+    // finished ones are returned
+    pub cancel: Option<CancellationToken>,
+}
+
+pub type ProgressCallback = Arc<dyn Fn(usize) + Send + Sync>;
+
+// Completion and effort times of each iteration
+pub type Outcomes = (Vec<Duration>, Vec<Duration>);
+
+impl fmt::Debug for SimulationOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SimulationOptions")
+            .field("seed", &self.seed)
+            .field("threads", &self.threads)
+            .field("progress", &self.progress.as_ref().map(|_| "Fn(usize)"))
+            .field("cancel", &self.cancel)
+            .finish()
+    }
+}
+
+impl SimulationOptions {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    // Runs `op` in a dedicated pool when `threads` is set
+    pub(crate) fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match self.threads {
+            Some(threads) => rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .expect("Failed to start simulation threads")
+                .install(op),
+            None => op(),
+        }
+    }
+}
+
+// Stops a running simulation early, e.g. from a Ctrl-C handler or another
+// thread. Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// Counts finished iterations for the progress callback, starting from those
+// finished by earlier batches
+pub(crate) struct ProgressCounter<'a> {
+    options: &'a SimulationOptions,
+    finished: AtomicUsize,
+}
+
+impl<'a> ProgressCounter<'a> {
+    pub fn new(options: &'a SimulationOptions, already_finished: usize) -> Self {
+        ProgressCounter {
+            options,
+            finished: AtomicUsize::new(already_finished),
+        }
+    }
+
+    // Runs one iteration unless the simulation has been cancelled
+    pub fn run(
+        &self,
+        compiled: &CompiledProject,
+        iteration: usize,
+    ) -> Option<Result<SimulationResult, String>> {
+        if self.options.is_cancelled() {
+            return None;
+        }
+        let result = run_iteration(compiled, self.options, iteration);
+        let finished = self.finished.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Some(progress) = &self.options.progress {
+            progress(finished);
+        }
+        Some(result)
+    }
 }
 
 pub fn run_multiple_simulations(
     schedule: &Project,
     num_simulations: usize,
-) -> (Vec<Duration>, Vec<Duration>) {
-    (0..num_simulations)
-        .into_par_iter()
-        .map(|_| {
-            let result = run_simulation(schedule);
-            (result.total_project_duration, result.total_effort_time)
-        })
-        .unzip()
+) -> Result<Outcomes, String> {
+    run_multiple_simulations_with_options(schedule, num_simulations, &SimulationOptions::default())
 }
 
-fn run_simulation(schedule: &Project) -> SimulationResult {
-    let mut rng = thread_rng();
+pub fn run_multiple_simulations_with_options(
+    schedule: &Project,
+    num_simulations: usize,
+    options: &SimulationOptions,
+) -> Result<Outcomes, String> {
+    run_multiple_range(schedule, options, 0..num_simulations)
+}
 
-    // Simulate task times
-    let task_effort_times: HashMap<_, _> = schedule
-        .tasks
-        .iter()
-        .map(|task| {
-            let task_time = simulate_task_time(&mut rng, task.min_time, task.max_time);
-            (&task.id, task_time)
+// Runs the iterations numbered `iterations`, so that consecutive batches give
+// the same results as one run of them all under a seed
+pub(crate) fn run_multiple_range(
+    schedule: &Project,
+    options: &SimulationOptions,
+    iterations: Range<usize>,
+) -> Result<Outcomes, String> {
+    let compiled = CompiledProject::new(schedule)?;
+    let counter = ProgressCounter::new(options, iterations.start);
+    let times: Vec<_> = options.install(|| {
+        iterations
+            .into_par_iter()
+            .filter_map(|iteration| {
+                let result = counter.run(&compiled, iteration)?;
+                Some(result.map(|r| (r.total_project_duration, r.total_effort_time)))
+            })
+            .collect::<Result<_, _>>()
+    })?;
+    Ok(times.into_iter().unzip())
+}
+
+pub fn run_detailed_simulations(
+    schedule: &Project,
+    num_simulations: usize,
+) -> Result<Vec<SimulationResult>, String> {
+    run_detailed_simulations_with_options(schedule, num_simulations, &SimulationOptions::default())
+}
+
+pub fn run_detailed_simulations_with_options(
+    schedule: &Project,
+    num_simulations: usize,
+    options: &SimulationOptions,
+) -> Result<Vec<SimulationResult>, String> {
+    run_detailed_range(schedule, options, 0..num_simulations)
+}
+
+pub(crate) fn run_detailed_range(
+    schedule: &Project,
+    options: &SimulationOptions,
+    iterations: Range<usize>,
+) -> Result<Vec<SimulationResult>, String> {
+    let compiled = CompiledProject::new(schedule)?;
+    let counter = ProgressCounter::new(options, iterations.start);
+    options.install(|| {
+        iterations
+            .into_par_iter()
+            .filter_map(|iteration| counter.run(&compiled, iteration))
+            .collect()
+    })
+}
+
+fn run_iteration(
+    compiled: &CompiledProject,
+    options: &SimulationOptions,
+    iteration: usize,
+) -> Result<SimulationResult, String> {
+    match options.seed {
+        Some(seed) => run_simulation(compiled, &mut iteration_rng(seed, iteration)),
+        None => run_simulation(compiled, &mut thread_rng()),
+    }
+}
+
+// Mixes the seed and iteration (SplitMix64) so that neighbouring seeds don't
+// share iterations
+fn iteration_rng(seed: u64, iteration: usize) -> StdRng {
+    let mut z = seed.wrapping_add((iteration as u64 + 1).wrapping_mul(0x9E3779B97F4A7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    StdRng::seed_from_u64(z ^ (z >> 31))
+}
+
+// What every iteration needs from a project, worked out once: tasks are
+// referred to by their index in `Project::tasks`, and the tasks waiting on
+// each task are stored in one flat list (compressed sparse rows)
+#[derive(Debug, Clone)]
+pub(crate) struct CompiledProject {
+    num_workers: usize,
+    durations: Vec<Pert>,
+    // Dependents of task i are `dependents[dependent_offsets[i]..dependent_offsets[i + 1]]`
+    dependent_offsets: Vec<usize>,
+    dependents: Vec<usize>,
+    // Number of dependencies of each task
+    in_degrees: Vec<usize>,
+    // Tasks with no dependencies, in project order
+    roots: Vec<usize>,
+    // The worker each task is reserved for, from its owner. Empty when no
+    // task is.
+    assignees: Vec<Option<usize>>,
+    // Calendar time at which each task may start. Empty when no task has a
+    // `not_before` date.
+    not_before: Vec<Duration>,
+    // Fixed dates of each task. Empty when no task has one.
+    fixed: Vec<Option<FixedDate>>,
+    // Range each worker's own focus is drawn from, or None for those sharing
+    // the project's. Empty when every worker is fully focused.
+    focus: Vec<Option<Focus>>,
+    // Range the project's focus is drawn from, once for everyone sharing it
+    team_focus: Focus,
+    // Interruptions per second of work, and how long each takes
+    interruptions: Option<(f64, Pert)>,
+    // Calendar dates and when each worker is available on them
+    timeline: Timeline,
+}
+
+// In working time on the project calendar
+#[derive(Debug, Clone, Copy)]
+enum FixedDate {
+    Start(Duration),
+    Finish(Duration),
+}
+
+impl CompiledProject {
+    // Fails when a worker, or the project calendar, has no working time
+    pub fn new(schedule: &Project) -> Result<Self, String> {
+        let index: HashMap<&str, usize> = schedule
+            .tasks
+            .iter()
+            .enumerate()
+            .map(|(i, task)| (task.id.as_str(), i))
+            .collect();
+
+        let mut in_degrees = vec![0; schedule.tasks.len()];
+        let mut dependent_offsets = vec![0; schedule.tasks.len() + 1];
+        for (i, task) in schedule.tasks.iter().enumerate() {
+            in_degrees[i] = task.dependencies.len();
+            for dep in &task.dependencies {
+                dependent_offsets[index[dep.as_str()] + 1] += 1;
+            }
+        }
+        for i in 0..schedule.tasks.len() {
+            dependent_offsets[i + 1] += dependent_offsets[i];
+        }
+        // Filled in project order, so each task's dependents are in project order
+        let mut next = dependent_offsets.clone();
+        let mut dependents = vec![0; dependent_offsets[schedule.tasks.len()]];
+        for (i, task) in schedule.tasks.iter().enumerate() {
+            for dep in &task.dependencies {
+                let d = index[dep.as_str()];
+                dependents[next[d]] = i;
+                next[d] += 1;
+            }
+        }
+
+        let workers: HashMap<&str, usize> = schedule
+            .workers
+            .iter()
+            .enumerate()
+            .map(|(i, worker)| (worker.name.as_str(), i))
+            .collect();
+        let mut assignees: Vec<_> = schedule
+            .tasks
+            .iter()
+            .map(|task| task.owner.as_deref().and_then(|o| workers.get(o).copied()))
+            .collect();
+        if assignees.iter().all(Option::is_none) {
+            assignees.clear();
+        }
+
+        let timeline = Timeline::new(schedule);
+        let mut not_before: Vec<_> = schedule
+            .tasks
+            .iter()
+            .map(|task| {
+                task.not_before
+                    .map_or(Duration::ZERO, |date| timeline.time_of(date))
+            })
+            .collect();
+        if not_before.iter().all(Duration::is_zero) {
+            not_before.clear();
+        }
+        let mut fixed: Vec<_> = schedule
+            .tasks
+            .iter()
+            .map(|task| match (task.fixed_start, task.fixed_finish) {
+                (Some(date), _) => Some(FixedDate::Start(
+                    timeline.workdays_at(timeline.time_of(date)),
+                )),
+                (None, Some(date)) => Some(FixedDate::Finish(timeline.workdays_by(date))),
+                (None, None) => None,
+            })
+            .collect();
+        if fixed.iter().all(Option::is_none) {
+            fixed.clear();
+        }
+        let mut focus: Vec<_> = if schedule.workers.is_empty() {
+            vec![None; schedule.num_workers]
+        } else {
+            schedule.workers.iter().map(|worker| worker.focus).collect()
+        };
+        if focus
+            .iter()
+            .all(|&focus| focus.unwrap_or(schedule.focus) == Focus::FULL)
+        {
+            focus.clear();
+        }
+        let interruptions = schedule
+            .interruptions
+            .as_ref()
+            .filter(|interruptions| interruptions.rate > 0.0)
+            .map(|interruptions| {
+                let durations = Pert::new(
+                    interruptions.min_time.as_secs_f64(),
+                    interruptions.likely_time.as_secs_f64(),
+                    interruptions.max_time.as_secs_f64(),
+                )
+                .unwrap();
+                (interruptions.rate / 86400.0, durations)
+            });
+
+        for worker in 0..schedule.num_workers {
+            timeline.next_available(worker, Duration::ZERO)?;
+        }
+        timeline.time_at(Duration::from_secs(1))?;
+
+        Ok(CompiledProject {
+            num_workers: schedule.num_workers,
+            durations: schedule
+                .tasks
+                .iter()
+                .map(|task| task_time_distribution(task.min_time, task.max_time))
+                .collect(),
+            dependent_offsets,
+            dependents,
+            roots: (0..schedule.tasks.len())
+                .filter(|&i| in_degrees[i] == 0)
+                .collect(),
+            in_degrees,
+            assignees,
+            not_before,
+            fixed,
+            focus,
+            team_focus: schedule.focus,
+            interruptions,
+            timeline,
         })
-        .collect();
+    }
 
-    let total_effort_time: Duration = task_effort_times.values().sum();
+    pub fn num_tasks(&self) -> usize {
+        self.durations.len()
+    }
+
+    fn dependents(&self, task: usize) -> &[usize] {
+        &self.dependents[self.dependent_offsets[task]..self.dependent_offsets[task + 1]]
+    }
+
+    // Whether `worker` may start `task` at `time`
+    fn may_take(&self, worker: usize, task: usize, time: Duration) -> bool {
+        let assignee = self.assignees.get(task).copied().flatten();
+        let not_before = self.not_before.get(task).copied().unwrap_or_default();
+        assignee.map_or(true, |w| w == worker) && not_before <= time
+    }
 
-    // Pre-compute task dependencies and reverse dependencies
-    let mut task_dependencies: HashMap<_, HashSet<_>> = HashMap::new();
-    let mut reverse_dependencies: HashMap<_, Vec<_>> = HashMap::new();
-    for task in &schedule.tasks {
-        task_dependencies.insert(&task.id, task.dependencies.iter().collect());
-        for dep in &task.dependencies {
-            reverse_dependencies.entry(dep).or_default().push(&task.id);
+    // How long `work` keeps `worker` busy at their `focus`, with interruptions
+    // arriving at random along the way
+    fn busy_time(
+        &self,
+        worker: usize,
+        work: Duration,
+        focus: &[f64],
+        rng: &mut impl Rng,
+    ) -> Duration {
+        if focus.is_empty() && self.interruptions.is_none() {
+            return work;
         }
+        let mut busy = work.as_secs_f64() / focus.get(worker).copied().unwrap_or(1.0);
+        if let Some((rate, durations)) = &self.interruptions {
+            let mut next = time_between(*rate, rng);
+            while next < busy {
+                let duration = durations.sample(rng);
+                busy += duration;
+                next += duration + time_between(*rate, rng);
+            }
+        }
+        Duration::from_secs_f64(busy)
+    }
+
+    // When `worker`, given a task at `time`, starts and finishes `work` of it
+    fn work(
+        &self,
+        worker: usize,
+        time: Duration,
+        work: Duration,
+    ) -> Result<(Duration, Duration), String> {
+        let start = self.timeline.next_available(worker, time)?;
+        Ok((start, self.timeline.finish(worker, start, work)?))
     }
 
-    // Initialize task queue with tasks that have no dependencies
-    let mut task_queue: Vec<_> = schedule
-        .tasks
+    // When a task with a fixed date, ready at `time`, starts and finishes
+    // `work` of it, which needs no worker. It slips when it's ready too late.
+    fn pin(
+        &self,
+        task: usize,
+        time: Duration,
+        work: Duration,
+    ) -> Result<Option<(Duration, Duration)>, String> {
+        let ready = self.timeline.workdays_at(time);
+        let start = match self.fixed.get(task).copied().flatten() {
+            Some(FixedDate::Start(start)) => start.max(ready),
+            Some(FixedDate::Finish(finish)) => finish.saturating_sub(work).max(ready),
+            None => return Ok(None),
+        };
+        Ok(Some((
+            self.timeline.time_at(start)?,
+            self.timeline.time_at(start + work)?,
+        )))
+    }
+}
+
+fn run_simulation(
+    schedule: &CompiledProject,
+    rng: &mut impl Rng,
+) -> Result<SimulationResult, String> {
+    let num_tasks = schedule.num_tasks();
+
+    // Simulate task times
+    let task_effort_times: Vec<_> = schedule
+        .durations
+        .iter()
+        .map(|pert| Duration::from_secs_f64(pert.sample(rng)))
+        .collect();
+
+    let total_effort_time: Duration = task_effort_times.iter().sum();
+
+    // How focused each worker is in this iteration. Workers without their
+    // own focus share the project's, so a distracted team is distracted
+    // together.
+    let team_focus = if schedule.focus.iter().any(Option::is_none) {
+        draw_focus(schedule.team_focus, rng)
+    } else {
+        1.0
+    };
+    let focus: Vec<f64> = schedule
+        .focus
         .iter()
-        .filter(|t| t.dependencies.is_empty())
-        .map(|t| &t.id)
+        .map(|focus| focus.map_or(team_focus, |focus| draw_focus(focus, rng)))
         .collect();
 
+    // Dependencies each task is still waiting on
+    let mut remaining = schedule.in_degrees.clone();
+
     let mut current_time = Duration::default();
-    let mut completed_tasks = HashSet::new();
+    let mut completed_tasks = 0;
+    let mut task_start_times = vec![Duration::default(); num_tasks];
+    let mut task_finish_times = vec![Duration::default(); num_tasks];
     let mut worker_finish_times = vec![Duration::default(); schedule.num_workers];
+    let mut worker_tasks: Vec<Option<usize>> = vec![None; schedule.num_workers];
+    // Tasks with fixed dates under way, and when they finish
+    let mut pinned: Vec<(usize, Duration)> = Vec::new();
+    let mut finished = Vec::new();
+
+    // Initialize task queue with tasks that have no dependencies, except those
+    // with fixed dates, which start straight away
+    let mut task_queue = Vec::with_capacity(schedule.roots.len());
+    for &task in &schedule.roots {
+        match schedule.pin(task, current_time, task_effort_times[task])? {
+            Some((start, finish)) => {
+                task_start_times[task] = start;
+                pinned.push((task, finish));
+            }
+            None => task_queue.push(task),
+        }
+    }
+
+    while completed_tasks < num_tasks {
+        // Assign queued tasks to all idle workers
+        for worker in 0..schedule.num_workers {
+            if task_queue.is_empty() {
+                break;
+            }
+            if worker_tasks[worker].is_some()
+                || schedule.timeline.next_available(worker, current_time)? != current_time
+            {
+                continue;
+            }
+            // Randomly choose the next task to assign, from those this worker
+            // may take
+            let task_index = if !schedule.assignees.is_empty() || !schedule.not_before.is_empty() {
+                let eligible: Vec<_> = (0..task_queue.len())
+                    .filter(|&i| schedule.may_take(worker, task_queue[i], current_time))
+                    .collect();
+                if eligible.is_empty() {
+                    continue;
+                }
+                eligible[rng.gen_range(0..eligible.len())]
+            } else {
+                rng.gen_range(0..task_queue.len())
+            };
+            let task = task_queue.swap_remove(task_index);
 
-    while !task_queue.is_empty() || completed_tasks.len() < schedule.tasks.len() {
-        // Find all available workers
-        let available_workers: Vec<_> = worker_finish_times
+            let busy = schedule.busy_time(worker, task_effort_times[task], &focus, rng);
+            let (start, finish) = schedule.work(worker, current_time, busy)?;
+            worker_finish_times[worker] = finish;
+            worker_tasks[worker] = Some(task);
+            task_start_times[task] = start;
+        }
+
+        // Move time forward to the next task completion, the next date a
+        // queued task may start on, or the next time an idle worker is back
+        let working = worker_tasks
             .iter()
-            .enumerate()
-            .filter(|&(_, &time)| time <= current_time)
-            .map(|(i, _)| i)
-            .collect();
+            .zip(&worker_finish_times)
+            .filter(|(task, _)| task.is_some())
+            .map(|(_, &time)| time);
+        let fixed = pinned.iter().map(|&(_, time)| time);
+        let waiting = task_queue
+            .iter()
+            .filter_map(|&task| schedule.not_before.get(task).copied())
+            .filter(|&time| time > current_time);
+        let mut next = working.chain(fixed).chain(waiting).min();
+        if !task_queue.is_empty() {
+            for worker in (0..schedule.num_workers).filter(|&w| worker_tasks[w].is_none()) {
+                let back = schedule.timeline.next_available(worker, current_time)?;
+                if back > current_time {
+                    next = Some(next.map_or(back, |time| time.min(back)));
+                }
+            }
+        }
+        current_time = next.unwrap_or(current_time);
 
-        if !task_queue.is_empty() && !available_workers.is_empty() {
-            for &worker in &available_workers {
-                if task_queue.is_empty() {
-                    break;
+        // Complete finished tasks, then add newly available tasks to queue
+        finished.clear();
+        for worker in 0..schedule.num_workers {
+            if worker_finish_times[worker] != current_time {
+                continue;
+            }
+            if let Some(task) = worker_tasks[worker].take() {
+                finished.push(task);
+            }
+        }
+        pinned.retain(|&(task, time)| {
+            if time == current_time {
+                finished.push(task);
+            }
+            time != current_time
+        });
+        for &task in &finished {
+            completed_tasks += 1;
+            task_finish_times[task] = current_time;
+
+            for &dependent in schedule.dependents(task) {
+                remaining[dependent] -= 1;
+                if remaining[dependent] > 0 {
+                    continue;
                 }
-                // Randomly choose the next task to assign
-                let task_index = rng.gen_range(0..task_queue.len());
-                let task_id = task_queue.swap_remove(task_index);
-
-                let task_duration = task_effort_times[task_id];
-                worker_finish_times[worker] = current_time + task_duration;
-                completed_tasks.insert(task_id);
-
-                // Add newly available tasks to queue
-                if let Some(dependent_tasks) = reverse_dependencies.get(task_id) {
-                    for &dep_task in dependent_tasks {
-                        if !completed_tasks.contains(dep_task)
-                            && !task_queue.contains(&dep_task)
-                            && task_dependencies[dep_task]
-                                .iter()
-                                .all(|dep| completed_tasks.contains(dep))
-                        {
-                            task_queue.push(dep_task);
-                        }
+                match schedule.pin(dependent, current_time, task_effort_times[dependent])? {
+                    Some((start, finish)) => {
+                        task_start_times[dependent] = start;
+                        pinned.push((dependent, finish));
                     }
+                    None => task_queue.push(dependent),
                 }
             }
         }
+    }
 
-        // Move time forward to the next event
-        current_time = *worker_finish_times.iter().min().unwrap_or(&current_time);
+    // Report calendar time as working time on the project calendar
+    let timeline = &schedule.timeline;
+    for time in task_start_times.iter_mut().chain(&mut task_finish_times) {
+        *time = timeline.workdays_at(*time);
     }
+    current_time = timeline.workdays_at(current_time);
 
-    SimulationResult {
-        total_project_duration: *worker_finish_times.iter().max().unwrap_or(&current_time),
+    Ok(SimulationResult {
+        total_project_duration: current_time,
         total_effort_time,
+        task_start_times,
+        task_finish_times,
+        task_effort_times,
+    })
+}
+
+fn draw_focus(focus: Focus, rng: &mut impl Rng) -> f64 {
+    if focus.min < focus.max {
+        rng.gen_range(focus.min..focus.max)
+    } else {
+        focus.min
     }
 }
 
-fn simulate_task_time(rng: &mut impl Rng, min_time: Duration, max_time: Duration) -> Duration {
+// Time until the next of a stream of events arriving at random at `rate`,
+// which is exponentially distributed
+fn time_between(rate: f64, rng: &mut impl Rng) -> f64 {
+    -(1.0 - rng.gen::<f64>()).ln() / rate
+}
+
+fn task_time_distribution(min_time: Duration, max_time: Duration) -> Pert {
     let min_secs = min_time.as_secs_f64();
     let max_secs = max_time.as_secs_f64();
     let mean = (min_secs + max_secs) / 2.0;
 
-    let pert = Pert::new(min_secs, mean, max_secs).unwrap();
-    let sampled_secs = pert.sample(rng);
-
-    Duration::from_secs_f64(sampled_secs)
+    Pert::new(min_secs, mean, max_secs).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::task::Task;
+    use crate::{
+        schedule::{Interruptions, Worker},
+        task::{days_to_duration, Task},
+    };
+    use chrono::NaiveDate;
 
     #[test]
     fn test_simulate_task_time() {
@@ -135,8 +651,9 @@ mod tests {
         let min_time = Duration::from_secs(5);
         let max_time = Duration::from_secs(15);
 
+        let pert = task_time_distribution(min_time, max_time);
         for _ in 0..1000 {
-            let time = simulate_task_time(&mut rng, min_time, max_time);
+            let time = Duration::from_secs_f64(pert.sample(&mut rng));
             assert!(
                 time >= min_time && time <= max_time,
                 "Simulated time should be within range"
@@ -144,6 +661,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_compiled_project() {
+        let task = |id: &str, deps: &[&str]| {
+            Task::new(
+                id,
+                deps.iter().map(|d| d.to_string()).collect(),
+                Duration::from_secs(1),
+                Duration::from_secs(2),
+                Duration::from_secs(3),
+            )
+        };
+        let schedule = Project::new(
+            vec![
+                task("D", &["B", "C"]),
+                task("B", &["A"]),
+                task("A", &[]),
+                task("C", &["A"]),
+                task("E", &[]),
+            ],
+            2,
+            None,
+        )
+        .unwrap();
+        let compiled = CompiledProject::new(&schedule).unwrap();
+
+        assert_eq!(compiled.num_tasks(), 5);
+        assert_eq!(compiled.roots, vec![2, 4]);
+        assert_eq!(compiled.in_degrees, vec![2, 1, 0, 1, 0]);
+        assert_eq!(compiled.dependents(0), &[] as &[usize]);
+        assert_eq!(compiled.dependents(1), &[0]);
+        assert_eq!(compiled.dependents(2), &[1, 3]);
+        assert_eq!(compiled.dependents(3), &[0]);
+    }
+
     #[test]
     fn test_single_task_simulation() {
         let task = Task::new(
@@ -155,7 +706,8 @@ mod tests {
         );
         let schedule = Project::new(vec![task], 1, None).unwrap();
 
-        let result = run_simulation(&schedule);
+        let result =
+            run_simulation(&CompiledProject::new(&schedule).unwrap(), &mut thread_rng()).unwrap();
 
         assert_eq!(
             result.total_project_duration, result.total_effort_time,
@@ -195,7 +747,8 @@ mod tests {
         ];
         let schedule = Project::new(tasks, 2, None).unwrap();
 
-        let result = run_simulation(&schedule);
+        let result =
+            run_simulation(&CompiledProject::new(&schedule).unwrap(), &mut thread_rng()).unwrap();
 
         assert!(
             result.total_project_duration <= result.total_effort_time,
@@ -241,15 +794,318 @@ mod tests {
         ];
         let schedule = Project::new(tasks, 2, None).unwrap();
 
-        let result = run_simulation(&schedule);
+        let result =
+            run_simulation(&CompiledProject::new(&schedule).unwrap(), &mut thread_rng()).unwrap();
         println!("{:?}", result);
 
         assert!(
-            result.total_project_duration >= Duration::from_secs(13),
+            result.total_project_duration >= Duration::from_secs(16),
             "Project duration should be at least the minimum critical path duration"
         );
     }
 
+    #[test]
+    fn test_dependents_start_after_dependencies_finish() {
+        let tasks = vec![
+            Task::new(
+                "A",
+                vec![],
+                Duration::from_secs(5),
+                Duration::from_secs(8),
+                Duration::from_secs(10),
+            ),
+            Task::new(
+                "B",
+                vec!["A".to_string()],
+                Duration::from_secs(7),
+                Duration::from_secs(9),
+                Duration::from_secs(12),
+            ),
+            Task::new(
+                "C",
+                vec!["A".to_string()],
+                Duration::from_secs(3),
+                Duration::from_secs(6),
+                Duration::from_secs(8),
+            ),
+        ];
+        let schedule = Project::new(tasks, 4, None).unwrap();
+
+        for _ in 0..100 {
+            let result =
+                run_simulation(&CompiledProject::new(&schedule).unwrap(), &mut thread_rng())
+                    .unwrap();
+            let start = &result.task_start_times;
+            let finish = &result.task_finish_times;
+            let effort = &result.task_effort_times;
+
+            for i in 0..3 {
+                assert_eq!(finish[i], start[i] + effort[i]);
+            }
+
+            assert_eq!(finish[0], effort[0], "A starts immediately");
+            assert_eq!(finish[1], finish[0] + effort[1], "B starts when A finishes");
+            assert_eq!(finish[2], finish[0] + effort[2], "C starts when A finishes");
+            assert_eq!(
+                result.total_project_duration,
+                finish.iter().copied().max().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_owned_tasks_wait_for_their_worker() {
+        let tasks = vec![
+            Task::new(
+                "A",
+                vec![],
+                days_to_duration(1.0),
+                days_to_duration(1.5),
+                days_to_duration(2.0),
+            ),
+            Task::new(
+                "B",
+                vec![],
+                days_to_duration(1.0),
+                days_to_duration(1.5),
+                days_to_duration(2.0),
+            ),
+        ];
+        let mut schedule = Project::new(tasks, 2, None).unwrap();
+        schedule.tasks[0].owner = Some("ann".to_string());
+        schedule.tasks[1].owner = Some("bo".to_string());
+        // Ann is away for the first week, and Bo works half days
+        let monday = NaiveDate::from_ymd_opt(2026, 11, 2).unwrap();
+        schedule.start_date = Some(monday);
+        let mut ann = Worker::new("ann");
+        ann.time_off = vec![(monday, monday + chrono::Days::new(4))];
+        let mut bo = Worker::new("bo");
+        bo.allocation = 0.5;
+        schedule.workers = vec![ann, bo];
+        let compiled = CompiledProject::new(&schedule).unwrap();
+
+        let close = |a: Duration, b: Duration| a.abs_diff(b) < Duration::from_millis(1);
+        for _ in 0..100 {
+            let result = run_simulation(&compiled, &mut thread_rng()).unwrap();
+            let start = &result.task_start_times;
+            let finish = &result.task_finish_times;
+            let effort = &result.task_effort_times;
+
+            assert!(close(start[0], days_to_duration(5.0)), "A waits for Ann");
+            assert!(close(finish[0], start[0] + effort[0]));
+            assert_eq!(start[1], Duration::ZERO);
+            assert!(close(finish[1], effort[1] * 2), "B takes Bo twice as long");
+        }
+    }
+
+    #[test]
+    fn test_free_worker_takes_tasks_while_others_are_away() {
+        let task = |id: &str| {
+            Task::new(
+                id,
+                vec![],
+                days_to_duration(1.0),
+                days_to_duration(1.5),
+                days_to_duration(2.0),
+            )
+        };
+        let mut schedule = Project::new(vec![task("A"), task("B")], 2, None).unwrap();
+        // Bo is away for the first three weeks
+        let monday = NaiveDate::from_ymd_opt(2026, 11, 2).unwrap();
+        schedule.start_date = Some(monday);
+        let mut bo = Worker::new("bo");
+        bo.time_off = vec![(monday, monday + chrono::Days::new(18))];
+        schedule.workers = vec![Worker::new("ann"), bo];
+        let compiled = CompiledProject::new(&schedule).unwrap();
+
+        let close = |a: Duration, b: Duration| a.abs_diff(b) < Duration::from_millis(1);
+        for _ in 0..100 {
+            let result = run_simulation(&compiled, &mut thread_rng()).unwrap();
+            let start = &result.task_start_times;
+            let finish = &result.task_finish_times;
+
+            // Ann does one task, then the other
+            let (first, second) = if start[0] < start[1] { (0, 1) } else { (1, 0) };
+            assert_eq!(start[first], Duration::ZERO);
+            assert!(close(start[second], finish[first]));
+            assert!(result.total_project_duration <= days_to_duration(4.0));
+        }
+    }
+
+    #[test]
+    fn test_date_constraints() {
+        let task = |id: &str, deps: &[&str]| {
+            Task::new(
+                id,
+                deps.iter().map(|d| d.to_string()).collect(),
+                days_to_duration(1.0),
+                days_to_duration(1.5),
+                days_to_duration(2.0),
+            )
+        };
+        let date = |d| NaiveDate::from_ymd_opt(2026, 11, d);
+        let mut tasks = vec![
+            task("A", &[]),
+            task("B", &[]),
+            task("C", &[]),
+            task("D", &["A"]),
+        ];
+        // Wednesday, and a week on Monday
+        tasks[0].not_before = date(4);
+        tasks[1].fixed_start = date(9);
+        // A week on Friday
+        tasks[2].fixed_finish = date(13);
+        // Tuesday, but A can't be done by then
+        tasks[3].fixed_start = date(3);
+        let mut schedule = Project::new(tasks, 1, None).unwrap();
+        schedule.start_date = date(2);
+        let compiled = CompiledProject::new(&schedule).unwrap();
+
+        let close = |a: Duration, b: Duration| a.abs_diff(b) < Duration::from_millis(1);
+        for _ in 0..100 {
+            let result = run_simulation(&compiled, &mut thread_rng()).unwrap();
+            let start = &result.task_start_times;
+            let finish = &result.task_finish_times;
+            let effort = &result.task_effort_times;
+
+            assert!(
+                close(start[0], days_to_duration(2.0)),
+                "A waits for Wednesday"
+            );
+            // Fixed tasks don't wait for the only worker
+            assert!(close(start[1], days_to_duration(5.0)));
+            assert!(close(finish[2], days_to_duration(10.0)));
+            assert!(close(start[2] + effort[2], finish[2]));
+            assert!(close(start[3], finish[0]), "D slips until A is done");
+            for i in 0..4 {
+                assert!(close(finish[i], start[i] + effort[i]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_focus_and_interruptions() {
+        let task = Task::new(
+            "A",
+            vec![],
+            days_to_duration(1.0),
+            days_to_duration(1.5),
+            days_to_duration(2.0),
+        );
+        let mut schedule = Project::new(vec![task], 1, None).unwrap();
+        // A Monday, leaving the rest of the week to work in
+        schedule.start_date = NaiveDate::from_ymd_opt(2026, 11, 2);
+        schedule.focus = Focus { min: 0.5, max: 0.8 };
+        let compiled = CompiledProject::new(&schedule).unwrap();
+
+        let close = |a: Duration, b: Duration| a.abs_diff(b) < Duration::from_millis(1);
+        for _ in 0..100 {
+            let result = run_simulation(&compiled, &mut thread_rng()).unwrap();
+            let effort = result.task_effort_times[0];
+            let days = result.total_project_duration.as_secs_f64() / effort.as_secs_f64();
+            assert!((1.25..=2.0).contains(&days), "{} times the effort", days);
+        }
+
+        schedule.focus = Focus::FULL;
+        schedule.workers = vec![Worker::new("Ann")];
+        schedule.workers[0].focus = Some(Focus::fixed(0.5));
+        let compiled = CompiledProject::new(&schedule).unwrap();
+        let result = run_simulation(&compiled, &mut thread_rng()).unwrap();
+        assert!(close(
+            result.total_project_duration,
+            result.task_effort_times[0] * 2
+        ));
+
+        // Around an hour of an eight hour day lost twice a day
+        schedule.workers[0].focus = None;
+        schedule.interruptions = Some(Interruptions {
+            rate: 2.0,
+            min_time: days_to_duration(0.5 / 8.0),
+            likely_time: days_to_duration(1.0 / 8.0),
+            max_time: days_to_duration(1.5 / 8.0),
+        });
+        let compiled = CompiledProject::new(&schedule).unwrap();
+        let mut lost = 0.0;
+        for _ in 0..1000 {
+            let result = run_simulation(&compiled, &mut thread_rng()).unwrap();
+            let effort = result.task_effort_times[0];
+            lost += (result.total_project_duration - effort).as_secs_f64() / effort.as_secs_f64();
+        }
+        let lost = lost / 1000.0;
+        assert!((0.2..0.3).contains(&lost), "{} more time", lost);
+    }
+
+    #[test]
+    fn test_team_shares_project_focus() {
+        let tasks = (0..40)
+            .map(|i| {
+                Task::new(
+                    &format!("T{}", i),
+                    vec![],
+                    days_to_duration(1.0),
+                    days_to_duration(1.5),
+                    days_to_duration(2.0),
+                )
+            })
+            .collect();
+        let mut schedule = Project::new(tasks, 4, None).unwrap();
+        schedule.start_date = NaiveDate::from_ymd_opt(2026, 11, 2);
+        let range = Focus { min: 0.5, max: 1.0 };
+        schedule.focus = range;
+
+        // Spread of completion time relative to the work done
+        let spread = |schedule: &Project| {
+            let compiled = CompiledProject::new(schedule).unwrap();
+            let ratios: Vec<f64> = (0..500)
+                .map(|_| {
+                    let result = run_simulation(&compiled, &mut thread_rng()).unwrap();
+                    result.total_project_duration.as_secs_f64()
+                        / result.total_effort_time.as_secs_f64()
+                })
+                .collect();
+            let mean = ratios.iter().sum::<f64>() / ratios.len() as f64;
+            let variance =
+                ratios.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / ratios.len() as f64;
+            variance.sqrt() / mean
+        };
+        let shared = spread(&schedule);
+
+        // The same range, drawn for each worker on their own
+        schedule.focus = Focus::FULL;
+        schedule.workers = (0..4)
+            .map(|i| {
+                let mut worker = Worker::new(&format!("W{}", i));
+                worker.focus = Some(range);
+                worker
+            })
+            .collect();
+        let independent = spread(&schedule);
+        assert!(
+            shared > independent * 1.5,
+            "spread {} with shared focus, {} without",
+            shared,
+            independent
+        );
+    }
+
+    #[test]
+    fn test_no_working_time_is_reported() {
+        let task = Task::new(
+            "A",
+            vec![],
+            days_to_duration(1.0),
+            days_to_duration(1.5),
+            days_to_duration(2.0),
+        );
+        let mut schedule = Project::new(vec![task], 1, None).unwrap();
+        schedule.start_date = NaiveDate::from_ymd_opt(2026, 11, 2);
+        schedule.calendar.set_work_days(&[]);
+
+        let error = Err("No working time after 2026-11-02".to_string());
+        assert_eq!(run_multiple_simulations(&schedule, 10), error);
+        assert!(CompiledProject::new(&schedule).is_err());
+    }
+
     #[test]
     fn test_multiple_simulations_consistency() {
         let tasks = vec![
@@ -270,7 +1126,7 @@ mod tests {
         ];
         let schedule = Project::new(tasks, 1, None).unwrap();
 
-        let (durations, efforts) = run_multiple_simulations(&schedule, 1000);
+        let (durations, efforts) = run_multiple_simulations(&schedule, 1000).unwrap();
 
         assert_eq!(durations.len(), 1000, "Should run 1000 simulations");
         assert_eq!(efforts.len(), 1000, "Should run 1000 simulations");
@@ -287,4 +1143,129 @@ mod tests {
             "Average effort should be within expected range"
         );
     }
+
+    #[test]
+    fn test_seeded_simulations_are_reproducible() {
+        let tasks = vec![
+            Task::new(
+                "A",
+                vec![],
+                Duration::from_secs(5),
+                Duration::from_secs(8),
+                Duration::from_secs(10),
+            ),
+            Task::new(
+                "B",
+                vec![],
+                Duration::from_secs(7),
+                Duration::from_secs(10),
+                Duration::from_secs(12),
+            ),
+        ];
+        let schedule = Project::new(tasks, 1, None).unwrap();
+        let seeded = |seed| SimulationOptions {
+            seed: Some(seed),
+            ..Default::default()
+        };
+
+        let first = run_multiple_simulations_with_options(&schedule, 500, &seeded(7)).unwrap();
+        let second = run_multiple_simulations_with_options(&schedule, 500, &seeded(7)).unwrap();
+        let other = run_multiple_simulations_with_options(&schedule, 500, &seeded(8)).unwrap();
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+
+        let detailed = run_detailed_simulations_with_options(&schedule, 500, &seeded(7)).unwrap();
+        let durations: Vec<_> = detailed.iter().map(|r| r.total_project_duration).collect();
+        assert_eq!(durations, first.0);
+
+        // However many threads run them
+        for threads in [1, 3] {
+            let options = SimulationOptions {
+                threads: Some(threads),
+                ..seeded(7)
+            };
+            let pooled = run_multiple_simulations_with_options(&schedule, 500, &options).unwrap();
+            assert_eq!(pooled, first);
+        }
+    }
+
+    #[test]
+    fn test_seeded_results_depend_on_start_date_only_through_the_calendar() {
+        let task = |id: &str| {
+            Task::new(
+                id,
+                vec![],
+                days_to_duration(1.0),
+                days_to_duration(2.5),
+                days_to_duration(4.0),
+            )
+        };
+        let mut schedule = Project::new(vec![task("A"), task("B"), task("C")], 2, None).unwrap();
+        let options = SimulationOptions {
+            seed: Some(7),
+            ..Default::default()
+        };
+        let run = |schedule: &mut Project, day| {
+            schedule.start_date = NaiveDate::from_ymd_opt(2026, 11, day);
+            run_multiple_simulations_with_options(schedule, 200, &options).unwrap()
+        };
+
+        // Workers who keep the project's hours finish after the same working
+        // time, whichever day the project starts on
+        let monday = run(&mut schedule, 2);
+        assert_eq!(run(&mut schedule, 5), monday);
+
+        // Holidays and workers' own days fall differently from other days
+        schedule.workers = vec![Worker::new("Ann"), Worker::new("Bo")];
+        schedule.workers[1].work_days = Some(vec![chrono::Weekday::Mon, chrono::Weekday::Tue]);
+        let monday = run(&mut schedule, 2);
+        assert_eq!(run(&mut schedule, 2), monday);
+        assert_ne!(run(&mut schedule, 5), monday);
+    }
+
+    #[test]
+    fn test_progress_and_cancellation() {
+        let task = Task::new(
+            "A",
+            vec![],
+            Duration::from_secs(5),
+            Duration::from_secs(8),
+            Duration::from_secs(10),
+        );
+        let schedule = Project::new(vec![task], 1, None).unwrap();
+
+        // Reports every finished iteration
+        let latest = Arc::new(AtomicUsize::new(0));
+        let seen = latest.clone();
+        let options = SimulationOptions {
+            progress: Some(Arc::new(move |finished| {
+                seen.fetch_max(finished, Ordering::Relaxed);
+            })),
+            ..Default::default()
+        };
+        let (durations, _) =
+            run_multiple_simulations_with_options(&schedule, 500, &options).unwrap();
+        assert_eq!(durations.len(), 500);
+        assert_eq!(latest.load(Ordering::Relaxed), 500);
+
+        // Cancelling part way returns what finished
+        let cancel = CancellationToken::default();
+        let token = cancel.clone();
+        let options = SimulationOptions {
+            progress: Some(Arc::new(move |finished| {
+                if finished == 100 {
+                    token.cancel();
+                }
+            })),
+            cancel: Some(cancel),
+            ..Default::default()
+        };
+        let results = run_detailed_simulations_with_options(&schedule, 100_000, &options).unwrap();
+        assert!(results.len() >= 100 && results.len() < 100_000);
+
+        // Nothing runs once cancelled
+        let (durations, _) =
+            run_multiple_simulations_with_options(&schedule, 500, &options).unwrap();
+        assert!(durations.is_empty());
+    }
 }
@@ -0,0 +1,355 @@
+// Mergeable quantile sketches (KLL), for summarizing huge numbers of
+// simulations without keeping every outcome
+//
+// A sketch keeps levels of sorted "compactors". Level h holds items that each
+// stand for 2^h outcomes; when a level fills up, it is sorted and every other
+// item, starting at random from the first or second, moves up a level. Each
+// compaction moves an item's rank by at most its weight, and the random offset
+// makes those moves cancel out on average. With accuracy parameter k the
+// estimated rank of any value is within about 1.7/k of the true rank with 99%
+// probability (Karnin, Lang and Liberty, "Optimal Quantile Approximation in
+// Streams", 2016), using O(k) memory however many outcomes go in. At the
+// default k of 200 that's a rank error of about 0.85%.
+use crate::{
+    schedule::Project,
+    simulation::{CompiledProject, ProgressCounter, SimulationOptions},
+};
+
+use rand::prelude::*;
+use rayon::prelude::*;
+
+use std::time::Duration;
+
+pub const DEFAULT_K: usize = 200;
+
+// Simulations per parallel chunk. Chunks are fixed so that seeded runs give
+// the same sketch whatever the number of threads.
+const CHUNK_SIZE: usize = 1 << 13;
+
+// Level capacities shrink by this factor going down from the top level
+const CAPACITY_DECAY: f64 = 2.0 / 3.0;
+
+#[derive(Debug, Clone)]
+pub struct QuantileSketch {
+    k: usize,
+    levels: Vec<Vec<Duration>>,
+    // Items held across all levels, and how many fit before compacting
+    size: usize,
+    max_size: usize,
+    count: u64,
+    min: Option<Duration>,
+    max: Option<Duration>,
+    rng: StdRng,
+}
+
+impl Default for QuantileSketch {
+    fn default() -> Self {
+        QuantileSketch::new(DEFAULT_K)
+    }
+}
+
+impl QuantileSketch {
+    pub fn new(k: usize) -> Self {
+        QuantileSketch::with_seed(k, 0)
+    }
+
+    // The seed picks the compaction offsets, so the same inputs and seed give
+    // the same sketch
+    pub fn with_seed(k: usize, seed: u64) -> Self {
+        let mut sketch = QuantileSketch {
+            k: k.max(2),
+            levels: Vec::new(),
+            size: 0,
+            max_size: 0,
+            count: 0,
+            min: None,
+            max: None,
+            rng: StdRng::seed_from_u64(seed),
+        };
+        sketch.grow();
+        sketch
+    }
+
+    fn capacity(&self, level: usize) -> usize {
+        let depth = self.levels.len() - level - 1;
+        (CAPACITY_DECAY.powi(depth as i32) * self.k as f64).ceil() as usize + 1
+    }
+
+    fn grow(&mut self) {
+        self.levels.push(Vec::new());
+        self.max_size = (0..self.levels.len()).map(|h| self.capacity(h)).sum();
+    }
+
+    fn compress(&mut self) {
+        for level in 0..self.levels.len() {
+            if self.levels[level].len() >= self.capacity(level) {
+                if level + 1 == self.levels.len() {
+                    self.grow();
+                }
+                let mut items = std::mem::take(&mut self.levels[level]);
+                items.sort_unstable();
+                // An odd item out stays behind
+                if items.len() % 2 == 1 {
+                    self.levels[level].push(items.pop().unwrap());
+                }
+                let offset = usize::from(self.rng.gen::<bool>());
+                let promoted = items.into_iter().skip(offset).step_by(2);
+                self.levels[level + 1].extend(promoted);
+                self.size = self.levels.iter().map(Vec::len).sum();
+                if self.size < self.max_size {
+                    break;
+                }
+            }
+        }
+    }
+
+    pub fn insert(&mut self, value: Duration) {
+        self.levels[0].push(value);
+        self.size += 1;
+        self.count += 1;
+        self.min = Some(self.min.map_or(value, |min| min.min(value)));
+        self.max = Some(self.max.map_or(value, |max| max.max(value)));
+        if self.size >= self.max_size {
+            self.compress();
+        }
+    }
+
+    pub fn merge(&mut self, other: QuantileSketch) {
+        while self.levels.len() < other.levels.len() {
+            self.grow();
+        }
+        for (level, items) in other.levels.into_iter().enumerate() {
+            self.levels[level].extend(items);
+        }
+        self.size = self.levels.iter().map(Vec::len).sum();
+        self.count += other.count;
+        self.min = match (self.min, other.min) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        // None orders before any value
+        self.max = self.max.max(other.max);
+        while self.size >= self.max_size {
+            self.compress();
+        }
+    }
+
+    // Number of outcomes inserted, including through merges
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    // Items with their weights, in order
+    fn weighted(&self) -> Vec<(Duration, u64)> {
+        let mut items: Vec<_> = self
+            .levels
+            .iter()
+            .enumerate()
+            .flat_map(|(level, items)| items.iter().map(move |&item| (item, 1u64 << level)))
+            .collect();
+        items.sort_unstable();
+        items
+    }
+
+    // Estimated values at each of `ps` (0.0 to 1.0), using the same nearest
+    // rank as `forecast::percentile`. 0.0 and 1.0 give the exact minimum and
+    // maximum.
+    pub fn quantiles(&self, ps: &[f64]) -> Vec<Duration> {
+        let items = self.weighted();
+        let total: u64 = items.iter().map(|(_, weight)| weight).sum();
+        ps.iter()
+            .map(|&p| {
+                if p <= 0.0 {
+                    return self.min.unwrap_or_default();
+                }
+                if p >= 1.0 {
+                    return self.max.unwrap_or_default();
+                }
+                // Items needed to reach the rank `percentile` would pick
+                let rank = (p * total.saturating_sub(1) as f64).round() as u64 + 1;
+                let mut seen = 0;
+                items
+                    .iter()
+                    .find(|(_, weight)| {
+                        seen += weight;
+                        seen >= rank
+                    })
+                    .map_or(Duration::ZERO, |&(item, _)| item)
+            })
+            .collect()
+    }
+
+    pub fn quantile(&self, p: f64) -> Duration {
+        self.quantiles(&[p])[0]
+    }
+
+    // Evenly spaced quantiles from the minimum to the maximum, sorted. Charts
+    // and statistics that take every outcome can take these instead.
+    pub fn sample(&self, points: usize) -> Vec<Duration> {
+        let last = points.max(2) - 1;
+        let ps: Vec<_> = (0..=last).map(|i| i as f64 / last as f64).collect();
+        self.quantiles(&ps)
+    }
+}
+
+// Like `run_multiple_simulations_with_options`, but summarizing the completion
+// and effort times in sketches instead of returning each one. Chunks of
+// simulations run in parallel, each into its own sketches, which are then
+// merged in order.
+pub fn run_sketched_simulations(
+    schedule: &Project,
+    num_simulations: usize,
+    options: &SimulationOptions,
+    k: usize,
+) -> Result<(QuantileSketch, QuantileSketch), String> {
+    let compiled = CompiledProject::new(schedule)?;
+    let counter = ProgressCounter::new(options, 0);
+    let chunks = num_simulations.div_ceil(CHUNK_SIZE);
+    let sketches: Vec<_> = options.install(|| {
+        (0..chunks)
+            .into_par_iter()
+            .map(|chunk| {
+                let seed = chunk as u64;
+                let mut durations = QuantileSketch::with_seed(k, seed);
+                let mut efforts = QuantileSketch::with_seed(k, seed);
+                let end = ((chunk + 1) * CHUNK_SIZE).min(num_simulations);
+                for iteration in chunk * CHUNK_SIZE..end {
+                    let Some(result) = counter.run(&compiled, iteration) else {
+                        break;
+                    };
+                    let result = result?;
+                    durations.insert(result.total_project_duration);
+                    efforts.insert(result.total_effort_time);
+                }
+                Ok((durations, efforts))
+            })
+            .collect::<Result<_, String>>()
+    })?;
+
+    let mut merged = (QuantileSketch::new(k), QuantileSketch::new(k));
+    for (durations, efforts) in sketches {
+        merged.0.merge(durations);
+        merged.1.merge(efforts);
+    }
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        forecast::percentile,
+        task::{days_to_duration, Task},
+    };
+
+    // Largest distance, as a fraction of all values, between the rank of each
+    // estimated percentile and the rank it should have
+    fn max_rank_error(sketch: &QuantileSketch, sorted: &[Duration]) -> f64 {
+        let ps: Vec<_> = (1..100).map(|i| i as f64 / 100.0).collect();
+        let n = sorted.len() as f64;
+        ps.iter()
+            .zip(sketch.quantiles(&ps))
+            .map(|(&p, estimate)| {
+                let below = sorted.partition_point(|&v| v < estimate) as f64;
+                let through = sorted.partition_point(|&v| v <= estimate) as f64;
+                let target = p * (n - 1.0);
+                if target < below {
+                    (below - target) / n
+                } else if target > through - 1.0 {
+                    (target - (through - 1.0)) / n
+                } else {
+                    0.0
+                }
+            })
+            .fold(0.0, f64::max)
+    }
+
+    fn shuffled(n: u64, seed: u64) -> Vec<Duration> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut values: Vec<_> = (0..n)
+            .map(|_| Duration::from_secs(rng.gen_range(0..1_000_000)))
+            .collect();
+        values.shuffle(&mut rng);
+        values
+    }
+
+    #[test]
+    fn test_small_inputs_are_exact() {
+        let values = shuffled(100, 1);
+        let mut sketch = QuantileSketch::new(DEFAULT_K);
+        for &value in &values {
+            sketch.insert(value);
+        }
+        let mut sorted = values.clone();
+        sorted.sort_unstable();
+
+        assert_eq!(sketch.count(), 100);
+        for p in [0.0, 0.1, 0.5, 0.85, 0.95, 1.0] {
+            assert_eq!(sketch.quantile(p), percentile(&sorted, p));
+        }
+        assert_eq!(sketch.sample(3), vec![sorted[0], sorted[50], sorted[99]]);
+    }
+
+    #[test]
+    fn test_accuracy_bound() {
+        for seed in 0..5 {
+            let values = shuffled(200_000, seed);
+            let mut sketch = QuantileSketch::with_seed(DEFAULT_K, seed);
+            for &value in &values {
+                sketch.insert(value);
+            }
+            let mut sorted = values;
+            sorted.sort_unstable();
+
+            assert!(max_rank_error(&sketch, &sorted) < 1.7 / DEFAULT_K as f64);
+            assert_eq!(sketch.quantile(0.0), sorted[0]);
+            assert_eq!(sketch.quantile(1.0), *sorted.last().unwrap());
+            // Memory stays proportional to k
+            assert!(sketch.size < 4 * DEFAULT_K);
+        }
+    }
+
+    #[test]
+    fn test_merged_accuracy_bound() {
+        let values = shuffled(200_000, 7);
+        let mut merged = QuantileSketch::new(DEFAULT_K);
+        for (i, chunk) in values.chunks(30_000).enumerate() {
+            let mut sketch = QuantileSketch::with_seed(DEFAULT_K, i as u64);
+            for &value in chunk {
+                sketch.insert(value);
+            }
+            merged.merge(sketch);
+        }
+        let mut sorted = values;
+        sorted.sort_unstable();
+
+        assert_eq!(merged.count(), 200_000);
+        assert!(max_rank_error(&merged, &sorted) < 1.7 / DEFAULT_K as f64);
+    }
+
+    #[test]
+    fn test_sketched_simulations_ignore_thread_count() {
+        let task = Task::new(
+            "A",
+            vec![],
+            days_to_duration(1.0),
+            days_to_duration(2.0),
+            days_to_duration(4.0),
+        );
+        let project = Project::new(vec![task], 1, None).unwrap();
+        let sketched = |threads| {
+            let options = SimulationOptions {
+                seed: Some(5),
+                threads: Some(threads),
+                ..Default::default()
+            };
+            let (durations, _) =
+                run_sketched_simulations(&project, 20_000, &options, DEFAULT_K).unwrap();
+            (durations.count(), durations.sample(101))
+        };
+
+        let single = sketched(1);
+        assert_eq!(single.0, 20_000);
+        assert_eq!(sketched(3), single);
+    }
+}
@@ -0,0 +1,171 @@
+// Summary statistics of simulated outcomes, and the single-point plan they
+// can be compared against
+use crate::schedule::Project;
+
+use std::{collections::HashMap, time::Duration};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: Duration,
+    pub std_dev: Duration,
+    // Positive when the long tail is on the late side
+    pub skewness: f64,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl Summary {
+    // Statistics of a non-empty set of outcomes, taken as the whole population
+    pub fn new(data: &[Duration]) -> Self {
+        let count = data.len();
+        let values: Vec<f64> = data.iter().map(Duration::as_secs_f64).collect();
+        let mean = values.iter().sum::<f64>() / count as f64;
+        let moment =
+            |power: i32| values.iter().map(|v| (v - mean).powi(power)).sum::<f64>() / count as f64;
+        let variance = moment(2);
+        let skewness = if variance > 0.0 {
+            moment(3) / variance.powf(1.5)
+        } else {
+            0.0
+        };
+        Summary {
+            count,
+            mean: Duration::from_secs_f64(mean),
+            std_dev: Duration::from_secs_f64(variance.sqrt()),
+            skewness,
+            min: *data.iter().min().unwrap(),
+            max: *data.iter().max().unwrap(),
+        }
+    }
+
+    // Standard deviation relative to the mean
+    pub fn coefficient_of_variation(&self) -> f64 {
+        if self.mean.is_zero() {
+            0.0
+        } else {
+            self.std_dev.as_secs_f64() / self.mean.as_secs_f64()
+        }
+    }
+}
+
+// The longest chain of dependent tasks when every task takes exactly its
+// likely estimate, regardless of how many workers there are
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CriticalPath {
+    pub duration: Duration,
+    // Indices of the tasks on the path, first to last
+    pub tasks: Vec<usize>,
+}
+
+pub fn likely_critical_path(project: &Project) -> CriticalPath {
+    let index: HashMap<&str, usize> = project
+        .tasks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (t.id.as_str(), i))
+        .collect();
+
+    // Earliest finish of each task and the dependency it waits for last
+    fn finish(
+        i: usize,
+        project: &Project,
+        index: &HashMap<&str, usize>,
+        finishes: &mut [Option<(Duration, Option<usize>)>],
+    ) -> Duration {
+        if let Some((time, _)) = finishes[i] {
+            return time;
+        }
+        let mut latest = (Duration::ZERO, None);
+        for dep in &project.tasks[i].dependencies {
+            let d = index[dep.as_str()];
+            let time = finish(d, project, index, finishes);
+            if latest.1.is_none() || time > latest.0 {
+                latest = (time, Some(d));
+            }
+        }
+        let time = latest.0 + project.tasks[i].likely_time;
+        finishes[i] = Some((time, latest.1));
+        time
+    }
+    let mut finishes = vec![None; project.tasks.len()];
+    let mut last = None;
+    for i in 0..project.tasks.len() {
+        let time = finish(i, project, &index, &mut finishes);
+        if last.map_or(true, |(longest, _)| time > longest) {
+            last = Some((time, i));
+        }
+    }
+
+    let Some((duration, mut i)) = last else {
+        return CriticalPath {
+            duration: Duration::ZERO,
+            tasks: Vec::new(),
+        };
+    };
+    let mut tasks = vec![i];
+    while let Some((_, Some(dep))) = finishes[i] {
+        tasks.push(dep);
+        i = dep;
+    }
+    tasks.reverse();
+    CriticalPath { duration, tasks }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{days_to_duration, Task};
+
+    #[test]
+    fn test_summary() {
+        let data: Vec<_> = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]
+            .iter()
+            .map(|&d| days_to_duration(d))
+            .collect();
+        let summary = Summary::new(&data);
+
+        assert_eq!(summary.count, 8);
+        assert_eq!(summary.mean, days_to_duration(5.0));
+        assert_eq!(summary.std_dev, days_to_duration(2.0));
+        assert_eq!(summary.coefficient_of_variation(), 0.4);
+        // Third central moment 5.25 over 2^3
+        assert!((summary.skewness - 0.65625).abs() < 1e-9);
+        assert_eq!(summary.min, days_to_duration(2.0));
+        assert_eq!(summary.max, days_to_duration(9.0));
+
+        let constant = Summary::new(&[days_to_duration(3.0); 4]);
+        assert_eq!(constant.std_dev, Duration::ZERO);
+        assert_eq!(constant.skewness, 0.0);
+    }
+
+    #[test]
+    fn test_likely_critical_path() {
+        let task = |id: &str, likely: f64, deps: &[&str]| {
+            Task::new(
+                id,
+                deps.iter().map(|d| d.to_string()).collect(),
+                days_to_duration(likely / 2.0),
+                days_to_duration(likely),
+                days_to_duration(likely * 2.0),
+            )
+        };
+        // A (2) feeds B (3) and C (5), which both feed D (1); E (4) is alone
+        let project = Project::new(
+            vec![
+                task("A", 2.0, &[]),
+                task("B", 3.0, &["A"]),
+                task("C", 5.0, &["A"]),
+                task("D", 1.0, &["B", "C"]),
+                task("E", 4.0, &[]),
+            ],
+            1,
+            None,
+        )
+        .unwrap();
+
+        let path = likely_critical_path(&project);
+        assert_eq!(path.duration, days_to_duration(8.0));
+        assert_eq!(path.tasks, vec![0, 2, 3]);
+    }
+}
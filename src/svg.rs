@@ -0,0 +1,505 @@
+// SVG charts shared by the HTML report and `mcps plot`. Output only depends on
+// the input data, so identical data gives byte-identical charts.
+use crate::{
+    forecast::{percentile, TaskForecast},
+    schedule::Project,
+    units::DisplayUnit,
+};
+
+use quick_xml::escape::escape;
+
+use std::{collections::HashMap, fmt::Write, time::Duration};
+
+pub const WIDTH: f64 = 640.0;
+pub const HEIGHT: f64 = 320.0;
+const MARGIN_LEFT: f64 = 56.0;
+const MARGIN_RIGHT: f64 = 24.0;
+const MARGIN_TOP: f64 = 36.0;
+const MARGIN_BOTTOM: f64 = 44.0;
+
+const FONT: &str = "font-family=\"sans-serif\" font-size=\"12\"";
+const LINE_COLOR: &str = "#1f77b4";
+const FILL_COLOR: &str = "#aec7e8";
+const GRID_COLOR: &str = "#dddddd";
+
+fn days(duration: Duration) -> f64 {
+    duration.as_secs_f64() / 86400.0
+}
+
+// Evenly spaced round values covering `min..=max`
+fn nice_ticks(min: f64, max: f64, target: usize) -> Vec<f64> {
+    let range = (max - min).max(f64::EPSILON);
+    let rough = range / target as f64;
+    let magnitude = 10f64.powf(rough.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * magnitude)
+        .find(|step| *step >= rough)
+        .unwrap_or(10.0 * magnitude);
+    // Allow for rounding error at either end
+    let first = (min / step - 1e-9).ceil() as i64;
+    let last = (max / step + 1e-9).floor() as i64;
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+fn format_tick(value: f64) -> String {
+    if (value - value.round()).abs() < 1e-9 {
+        format!("{}", value.round())
+    } else {
+        format!("{:.1}", value)
+    }
+}
+
+// Maps data values to pixel positions inside the plot area
+struct Axes {
+    x_min: f64,
+    x_max: f64,
+    y_max: f64,
+}
+
+impl Axes {
+    fn new(x_min: f64, x_max: f64, y_max: f64) -> Self {
+        // Give single-valued data some width to draw in
+        let (x_min, x_max) = if x_max - x_min < 1e-9 {
+            (x_min - 0.5, x_max + 0.5)
+        } else {
+            (x_min, x_max)
+        };
+        Axes {
+            x_min,
+            x_max,
+            y_max,
+        }
+    }
+
+    fn x(&self, value: f64) -> f64 {
+        let width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+        MARGIN_LEFT + (value - self.x_min) / (self.x_max - self.x_min) * width
+    }
+
+    fn y(&self, value: f64) -> f64 {
+        let height = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+        HEIGHT - MARGIN_BOTTOM - value / self.y_max * height
+    }
+
+    // Frame, grid, tick labels and titles
+    fn draw(&self, svg: &mut String, title: &str, x_label: &str, y_ticks: &[(f64, String)]) {
+        let (left, right) = (MARGIN_LEFT, WIDTH - MARGIN_RIGHT);
+        let (top, bottom) = (MARGIN_TOP, HEIGHT - MARGIN_BOTTOM);
+        for (value, label) in y_ticks {
+            let y = self.y(*value);
+            writeln!(svg, "<line x1=\"{left:.1}\" y1=\"{y:.1}\" x2=\"{right:.1}\" y2=\"{y:.1}\" stroke=\"{GRID_COLOR}\"/>").unwrap();
+            writeln!(
+                svg,
+                "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\" {FONT}>{label}</text>",
+                left - 6.0,
+                y + 4.0
+            )
+            .unwrap();
+        }
+        for tick in nice_ticks(self.x_min, self.x_max, 8) {
+            let x = self.x(tick);
+            writeln!(svg, "<line x1=\"{x:.1}\" y1=\"{bottom:.1}\" x2=\"{x:.1}\" y2=\"{:.1}\" stroke=\"#000000\"/>", bottom + 4.0).unwrap();
+            writeln!(
+                svg,
+                "<text x=\"{x:.1}\" y=\"{:.1}\" text-anchor=\"middle\" {FONT}>{}</text>",
+                bottom + 18.0,
+                format_tick(tick)
+            )
+            .unwrap();
+        }
+        writeln!(svg, "<rect x=\"{left:.1}\" y=\"{top:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"none\" stroke=\"#000000\"/>", right - left, bottom - top).unwrap();
+        writeln!(svg, "<text x=\"{:.1}\" y=\"22\" text-anchor=\"middle\" font-family=\"sans-serif\" font-size=\"15\" font-weight=\"bold\">{}</text>", WIDTH / 2.0, escape(title)).unwrap();
+        writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" {FONT}>{x_label}</text>",
+            (left + right) / 2.0,
+            HEIGHT - 8.0
+        )
+        .unwrap();
+    }
+}
+
+fn open(svg: &mut String, width: f64, height: f64) {
+    writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.0}\" height=\"{height:.0}\" viewBox=\"0 0 {width:.0} {height:.0}\">").unwrap();
+    writeln!(
+        svg,
+        "<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>"
+    )
+    .unwrap();
+}
+
+// Cumulative probability of finishing within each amount of time, in `unit`
+pub fn cdf(sorted: &[Duration], title: &str, unit: DisplayUnit) -> String {
+    let axes = Axes::new(
+        unit.value(sorted[0]),
+        unit.value(*sorted.last().unwrap()),
+        1.0,
+    );
+    let mut svg = String::new();
+    open(&mut svg, WIDTH, HEIGHT);
+    let y_ticks: Vec<_> = (0..=4)
+        .map(|i| (i as f64 / 4.0, format!("{}%", i * 25)))
+        .collect();
+    axes.draw(&mut svg, title, unit.heading(), &y_ticks);
+
+    let points: Vec<String> = (0..=100)
+        .map(|i| {
+            let p = i as f64 / 100.0;
+            format!(
+                "{:.1},{:.1}",
+                axes.x(unit.value(percentile(sorted, p))),
+                axes.y(p)
+            )
+        })
+        .collect();
+    writeln!(
+        svg,
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"{LINE_COLOR}\" stroke-width=\"2\"/>",
+        points.join(" ")
+    )
+    .unwrap();
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// Counts per bin of equal width between the smallest and largest value
+pub fn bin_counts(sorted: &[Duration], bins: usize) -> Vec<usize> {
+    let (min, max) = (days(sorted[0]), days(*sorted.last().unwrap()));
+    let width = (max - min) / bins as f64;
+    let mut counts = vec![0; bins];
+    for &value in sorted {
+        let bin = if width > 0.0 {
+            ((days(value) - min) / width) as usize
+        } else {
+            0
+        };
+        counts[bin.min(bins - 1)] += 1;
+    }
+    counts
+}
+
+// Share of outcomes per range of time, in `unit`
+pub fn histogram(sorted: &[Duration], bins: usize, title: &str, unit: DisplayUnit) -> String {
+    let (min, max) = (unit.value(sorted[0]), unit.value(*sorted.last().unwrap()));
+    let counts = bin_counts(sorted, bins);
+    let shares: Vec<f64> = counts
+        .iter()
+        .map(|&c| c as f64 / sorted.len() as f64)
+        .collect();
+    let top = shares.iter().cloned().fold(0.0, f64::max);
+    let y_max = nice_ticks(0.0, top, 4)
+        .last()
+        .copied()
+        .filter(|t| *t >= top)
+        .unwrap_or(top * 1.1);
+    let axes = Axes::new(min, max, y_max);
+
+    let mut svg = String::new();
+    open(&mut svg, WIDTH, HEIGHT);
+    let y_ticks: Vec<_> = nice_ticks(0.0, y_max, 4)
+        .into_iter()
+        .map(|t| (t, format!("{}%", format_tick(t * 100.0))))
+        .collect();
+    axes.draw(&mut svg, title, unit.heading(), &y_ticks);
+
+    let bin_width = (axes.x_max - axes.x_min) / bins as f64;
+    for (i, share) in shares.iter().enumerate() {
+        let x0 = axes.x(axes.x_min + i as f64 * bin_width);
+        let x1 = axes.x(axes.x_min + (i + 1) as f64 * bin_width);
+        let y = axes.y(*share);
+        writeln!(
+            svg,
+            "<rect x=\"{x0:.1}\" y=\"{y:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{FILL_COLOR}\" stroke=\"{LINE_COLOR}\"/>",
+            x1 - x0,
+            axes.y(0.0) - y
+        )
+        .unwrap();
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+const GANTT_WIDTH: f64 = 800.0;
+const GANTT_LABEL_WIDTH: f64 = 200.0;
+const GANTT_ROW_HEIGHT: f64 = 22.0;
+
+// One row per task with a light bar from its p10 start to its p90 finish and a
+// dark bar from its p50 start to its p50 finish, on a time axis in `unit`
+pub fn gantt(
+    project: &Project,
+    forecasts: &[TaskForecast],
+    title: &str,
+    unit: DisplayUnit,
+) -> String {
+    let end = forecasts
+        .iter()
+        .map(|f| unit.value(f.finish(0.9)))
+        .fold(0.0, f64::max)
+        .max(f64::EPSILON);
+    let (left, right) = (GANTT_LABEL_WIDTH + 10.0, GANTT_WIDTH - MARGIN_RIGHT);
+    let x = |value: f64| left + value / end * (right - left);
+    let top = MARGIN_TOP + 8.0;
+    let bottom = top + project.tasks.len() as f64 * GANTT_ROW_HEIGHT;
+    let height = bottom + MARGIN_BOTTOM;
+
+    let mut svg = String::new();
+    open(&mut svg, GANTT_WIDTH, height);
+    writeln!(svg, "<text x=\"{:.1}\" y=\"22\" text-anchor=\"middle\" font-family=\"sans-serif\" font-size=\"15\" font-weight=\"bold\">{}</text>", GANTT_WIDTH / 2.0, escape(title)).unwrap();
+    for tick in nice_ticks(0.0, end, 8) {
+        let tick_x = x(tick);
+        writeln!(svg, "<line x1=\"{tick_x:.1}\" y1=\"{top:.1}\" x2=\"{tick_x:.1}\" y2=\"{bottom:.1}\" stroke=\"{GRID_COLOR}\"/>").unwrap();
+        writeln!(
+            svg,
+            "<text x=\"{tick_x:.1}\" y=\"{:.1}\" text-anchor=\"middle\" {FONT}>{}</text>",
+            bottom + 18.0,
+            format_tick(tick)
+        )
+        .unwrap();
+    }
+    writeln!(
+        svg,
+        "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" {FONT}>{} (p10-p90 light, p50 dark)</text>",
+        (left + right) / 2.0,
+        height - 8.0,
+        unit.heading()
+    )
+    .unwrap();
+
+    for (row, (task, forecast)) in project.tasks.iter().zip(forecasts).enumerate() {
+        let y = top + row as f64 * GANTT_ROW_HEIGHT;
+        writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\" {FONT}>{}</text>",
+            GANTT_LABEL_WIDTH,
+            y + GANTT_ROW_HEIGHT / 2.0 + 4.0,
+            escape(truncate(task.display_name(), 28))
+        )
+        .unwrap();
+        for (from, to, inset, color) in [
+            (forecast.start(0.1), forecast.finish(0.9), 4.0, FILL_COLOR),
+            (forecast.start(0.5), forecast.finish(0.5), 7.0, LINE_COLOR),
+        ] {
+            let (x0, x1) = (x(unit.value(from)), x(unit.value(to)));
+            writeln!(
+                svg,
+                "<rect x=\"{x0:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{color}\"/>",
+                y + inset,
+                (x1 - x0).max(1.0),
+                GANTT_ROW_HEIGHT - 2.0 * inset
+            )
+            .unwrap();
+        }
+    }
+    writeln!(svg, "<line x1=\"{left:.1}\" y1=\"{bottom:.1}\" x2=\"{right:.1}\" y2=\"{bottom:.1}\" stroke=\"#000000\"/>").unwrap();
+    svg.push_str("</svg>\n");
+    svg
+}
+
+const NODE_WIDTH: f64 = 150.0;
+const NODE_HEIGHT: f64 = 30.0;
+const COLUMN_SPACING: f64 = 200.0;
+const ROW_SPACING: f64 = 44.0;
+
+fn truncate(label: &str, length: usize) -> String {
+    if label.chars().count() <= length {
+        return label.to_string();
+    }
+    let mut truncated: String = label.chars().take(length - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+// Tasks and milestones laid out left to right by dependency depth, each task
+// shaded by `criticality` (0.0..=1.0, indexed like `Project::tasks`)
+pub fn dependency_graph(project: &Project, criticality: &[f64]) -> String {
+    let index: HashMap<&str, usize> = project
+        .tasks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (t.id.as_str(), i))
+        .collect();
+
+    // Column of each task is the length of the longest chain of dependencies before it
+    let mut columns = vec![0; project.tasks.len()];
+    fn column(
+        i: usize,
+        project: &Project,
+        index: &HashMap<&str, usize>,
+        columns: &mut [usize],
+        done: &mut [bool],
+    ) -> usize {
+        if !done[i] {
+            columns[i] = project.tasks[i]
+                .dependencies
+                .iter()
+                .map(|dep| column(index[dep.as_str()], project, index, columns, done) + 1)
+                .max()
+                .unwrap_or(0);
+            done[i] = true;
+        }
+        columns[i]
+    }
+    let mut done = vec![false; project.tasks.len()];
+    for i in 0..project.tasks.len() {
+        column(i, project, &index, &mut columns, &mut done);
+    }
+    let milestone_columns: Vec<usize> = project
+        .milestones
+        .iter()
+        .map(|m| {
+            m.dependencies
+                .iter()
+                .map(|dep| columns[index[dep.as_str()]] + 1)
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    // Position every node, in project order within each column
+    let mut rows: HashMap<usize, usize> = HashMap::new();
+    let mut place = |column: usize| {
+        let row = rows.entry(column).or_default();
+        *row += 1;
+        (
+            20.0 + column as f64 * COLUMN_SPACING,
+            20.0 + (*row - 1) as f64 * ROW_SPACING,
+        )
+    };
+    let task_positions: Vec<_> = columns.iter().map(|&c| place(c)).collect();
+    let milestone_positions: Vec<_> = milestone_columns.iter().map(|&c| place(c)).collect();
+    let last_column = *columns.iter().chain(&milestone_columns).max().unwrap_or(&0);
+    let width = 40.0 + last_column as f64 * COLUMN_SPACING + NODE_WIDTH;
+    let height = 40.0 + (rows.values().max().unwrap_or(&1) - 1) as f64 * ROW_SPACING + NODE_HEIGHT;
+
+    let mut svg = String::new();
+    open(&mut svg, width, height);
+    writeln!(svg, "<g fill=\"none\" stroke=\"#888888\">").unwrap();
+    let mut edge = |from: (f64, f64), to: (f64, f64)| {
+        let (x0, y0) = (from.0 + NODE_WIDTH, from.1 + NODE_HEIGHT / 2.0);
+        let (x1, y1) = (to.0, to.1 + NODE_HEIGHT / 2.0);
+        let middle = (x0 + x1) / 2.0;
+        writeln!(
+            svg,
+            "<path d=\"M{x0:.1},{y0:.1} C{middle:.1},{y0:.1} {middle:.1},{y1:.1} {x1:.1},{y1:.1}\"/>"
+        )
+        .unwrap();
+    };
+    for (task, &to) in project.tasks.iter().zip(&task_positions) {
+        for dep in &task.dependencies {
+            edge(task_positions[index[dep.as_str()]], to);
+        }
+    }
+    for (milestone, &to) in project.milestones.iter().zip(&milestone_positions) {
+        for dep in &milestone.dependencies {
+            edge(task_positions[index[dep.as_str()]], to);
+        }
+    }
+    writeln!(svg, "</g>").unwrap();
+
+    for (i, (task, (x, y))) in project.tasks.iter().zip(&task_positions).enumerate() {
+        let shade = criticality.get(i).copied().unwrap_or(0.0);
+        writeln!(
+            svg,
+            "<rect x=\"{x:.1}\" y=\"{y:.1}\" width=\"{NODE_WIDTH}\" height=\"{NODE_HEIGHT}\" rx=\"4\" fill=\"#d62728\" fill-opacity=\"{:.2}\" stroke=\"#444444\"><title>{}</title></rect>",
+            0.1 + 0.6 * shade,
+            escape(format!("{} ({:.0}% critical)", task.display_name(), shade * 100.0))
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" {FONT}>{}</text>",
+            x + NODE_WIDTH / 2.0,
+            y + NODE_HEIGHT / 2.0 + 4.0,
+            escape(truncate(task.display_name(), 22))
+        )
+        .unwrap();
+    }
+    for (milestone, (x, y)) in project.milestones.iter().zip(&milestone_positions) {
+        let (cx, cy) = (x + NODE_HEIGHT / 2.0, y + NODE_HEIGHT / 2.0);
+        let r = NODE_HEIGHT / 2.0;
+        writeln!(
+            svg,
+            "<path d=\"M{cx:.1},{:.1} L{:.1},{cy:.1} L{cx:.1},{:.1} L{:.1},{cy:.1} Z\" fill=\"#2ca02c\" stroke=\"#444444\"/>",
+            cy - r,
+            cx + r,
+            cy + r,
+            cx - r
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" {FONT}>{}</text>",
+            x + NODE_HEIGHT + 4.0,
+            cy + 4.0,
+            escape(truncate(milestone.display_name(), 18))
+        )
+        .unwrap();
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{days_to_duration, Task};
+
+    #[test]
+    fn test_nice_ticks() {
+        assert_eq!(
+            nice_ticks(0.0, 10.0, 5),
+            vec![0.0, 2.0, 4.0, 6.0, 8.0, 10.0]
+        );
+        assert_eq!(nice_ticks(13.2, 27.9, 4), vec![15.0, 20.0, 25.0]);
+        let ticks = nice_ticks(0.0, 0.3, 3);
+        assert_eq!(ticks.len(), 4);
+        assert!((ticks[3] - 0.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_bin_counts() {
+        let sorted: Vec<_> = [1.0, 1.5, 2.0, 2.0, 4.0]
+            .iter()
+            .map(|&d| days_to_duration(d))
+            .collect();
+        assert_eq!(bin_counts(&sorted, 3), vec![2, 2, 1]);
+        assert_eq!(bin_counts(&sorted[2..4], 3), vec![2, 0, 0]);
+    }
+
+    #[test]
+    fn test_charts_are_deterministic() {
+        let sorted: Vec<_> = (0..100)
+            .map(|i| days_to_duration(i as f64 / 10.0))
+            .collect();
+        let unit = DisplayUnit::default();
+        assert_eq!(cdf(&sorted, "Title", unit), cdf(&sorted, "Title", unit));
+        assert!(cdf(&sorted, "A & B", unit).contains("A &amp; B"));
+        assert!(histogram(&sorted, 10, "Title", unit).starts_with("<svg"));
+    }
+
+    #[test]
+    fn test_dependency_graph_layout() {
+        let task = |id: &str, deps: &[&str]| {
+            Task::new(
+                id,
+                deps.iter().map(|d| d.to_string()).collect(),
+                days_to_duration(1.0),
+                days_to_duration(2.0),
+                days_to_duration(3.0),
+            )
+        };
+        let project = Project::new(
+            vec![task("A", &[]), task("B", &["A"]), task("C", &["A", "B"])],
+            1,
+            None,
+        )
+        .unwrap();
+        let svg = dependency_graph(&project, &[1.0, 0.0, 1.0]);
+        // Three columns, one node each
+        assert!(svg.contains("<rect x=\"20.0\" y=\"20.0\""));
+        assert!(svg.contains("<rect x=\"220.0\" y=\"20.0\""));
+        assert!(svg.contains("<rect x=\"420.0\" y=\"20.0\""));
+        assert_eq!(svg.matches("<path").count(), 3);
+    }
+}
@@ -1,12 +1,31 @@
-use std::time::Duration;
+use chrono::NaiveDate;
 
-#[derive(Debug, Clone)]
+use std::{collections::BTreeMap, path::PathBuf, time::Duration};
+
+#[derive(Debug, Clone, Default)]
 pub struct Task {
     pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub owner: Option<String>,
+    pub tags: Vec<String>,
+    pub metadata: BTreeMap<String, serde_json::Value>,
+    // Id of the enclosing `WbsNode`, if any
+    pub parent: Option<String>,
+    // File the task was loaded from, used to point errors at the right file
+    pub source: Option<PathBuf>,
     pub dependencies: Vec<String>,
     pub min_time: Duration,
     pub likely_time: Duration,
     pub max_time: Duration,
+    // Date constraints, on the project's calendar. Work can't start before
+    // `not_before`; a fixed start or finish pins the task to that date, without
+    // waiting for a worker; `must_finish_by` is a deadline that is only
+    // reported on.
+    pub not_before: Option<NaiveDate>,
+    pub fixed_start: Option<NaiveDate>,
+    pub fixed_finish: Option<NaiveDate>,
+    pub must_finish_by: Option<NaiveDate>,
 }
 
 impl Task {
@@ -23,8 +42,22 @@ impl Task {
             min_time,
             likely_time,
             max_time,
+            ..Default::default()
         }
     }
+
+    // Task id plus the file it came from, for error messages
+    pub fn describe(&self) -> String {
+        match &self.source {
+            Some(source) => format!("{} (in {})", self.id, source.display()),
+            None => self.id.clone(),
+        }
+    }
+
+    // Human readable label for reports, falling back to the id when no name is given
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.id)
+    }
 }
 
 pub fn days_to_duration(days: f64) -> Duration {
@@ -112,6 +145,53 @@ mod tests {
         assert_eq!(task1.max_time, task2.max_time);
     }
 
+    #[test]
+    fn test_task_creation_has_no_descriptive_fields() {
+        let task = Task::new(
+            "Task5",
+            vec![],
+            Duration::from_secs(1),
+            Duration::from_secs(2),
+            Duration::from_secs(3),
+        );
+
+        assert!(task.name.is_none());
+        assert!(task.description.is_none());
+        assert!(task.owner.is_none());
+        assert!(task.tags.is_empty());
+        assert!(task.metadata.is_empty());
+    }
+
+    #[test]
+    fn test_display_name() {
+        let mut task = Task::new(
+            "T-417",
+            vec![],
+            Duration::from_secs(1),
+            Duration::from_secs(2),
+            Duration::from_secs(3),
+        );
+        assert_eq!(task.display_name(), "T-417");
+
+        task.name = Some("Implement OAuth flow".to_string());
+        assert_eq!(task.display_name(), "Implement OAuth flow");
+    }
+
+    #[test]
+    fn test_describe() {
+        let mut task = Task::new(
+            "Auth",
+            vec![],
+            Duration::from_secs(1),
+            Duration::from_secs(2),
+            Duration::from_secs(3),
+        );
+        assert_eq!(task.describe(), "Auth");
+
+        task.source = Some(PathBuf::from("backend/tasks.yaml"));
+        assert_eq!(task.describe(), "Auth (in backend/tasks.yaml)");
+    }
+
     #[test]
     fn test_days_to_duration() {
         assert_duration_eq(days_to_duration(1.0), Duration::from_secs(24 * 60 * 60));
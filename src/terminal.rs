@@ -0,0 +1,939 @@
+// Text charts of simulated durations for the terminal
+use crate::{
+    calendar::Calendar,
+    convergence::Convergence,
+    forecast::{finish_date_after, percentile, DeadlineForecast},
+    stats::Summary,
+    svg::bin_counts,
+    task::days_to_duration,
+    units::DisplayUnit,
+};
+
+use chrono::NaiveDate;
+
+use std::{fmt::Write, str::FromStr, time::Duration};
+
+// Columns used by everything but the bar: the dividers, the workdays,
+// schedule and date columns, and the interval annotation
+const FIXED_COLUMNS: usize = 4 + 3 * 10 + 16;
+// The same for the compact layout, which drops the schedule column and
+// shortens the annotation
+const COMPACT_FIXED_COLUMNS: usize = 3 + 2 * 10 + 8;
+// Terminals narrower than this get the compact layout
+const COMPACT_BELOW: usize = 100;
+const MIN_BAR_WIDTH: usize = 20;
+const MAX_BAR_WIDTH: usize = 120;
+
+// Columns used by everything but the histogram bars: the bin ranges, the
+// dividers, the shares and the mean, median and mode marks
+const HISTOGRAM_FIXED_COLUMNS: usize = 16 + 2 + 8 + 22;
+const RANGE_WIDTH: usize = 16;
+const SHARE_WIDTH: usize = 8;
+
+struct Glyphs {
+    rule: &'static str,
+    divider: &'static str,
+    // Where rules meet dividers at the top, middle and bottom of a table
+    joins: [&'static str; 3],
+    // Bar fill before and after the marker, alternating by row
+    fills: [(char, char); 2],
+    marker: char,
+    arrow: &'static str,
+    // Interval bracket lines, corners (top, bottom) and tee
+    heavy: &'static str,
+    side: &'static str,
+    corners: [&'static str; 2],
+    tee: &'static str,
+    block: &'static str,
+    // Partial blocks in eighths, for bars drawn to sub-character precision
+    eighths: Option<[char; 8]>,
+}
+
+const UNICODE: Glyphs = Glyphs {
+    rule: "─",
+    divider: "│",
+    joins: ["┬", "┼", "┴"],
+    fills: [('░', '▓'), ('▒', '█')],
+    marker: '▮',
+    arrow: "◀",
+    heavy: "━",
+    side: "┃",
+    corners: ["┓", "┛"],
+    tee: "┣",
+    block: "█",
+    eighths: Some([' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉']),
+};
+
+const ASCII: Glyphs = Glyphs {
+    rule: "-",
+    divider: "|",
+    joins: ["+", "+", "+"],
+    fills: [('=', '.'), ('-', ' ')],
+    marker: '#',
+    arrow: "<",
+    heavy: "-",
+    side: "|",
+    corners: ["+", "+"],
+    tee: "+",
+    block: "#",
+    eighths: None,
+};
+
+// Whether charts use ANSI colors, and Unicode box drawing and block
+// characters or plain ASCII
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    pub color: bool,
+    pub ascii: bool,
+}
+
+impl Style {
+    fn glyphs(&self) -> &'static Glyphs {
+        if self.ascii {
+            &ASCII
+        } else {
+            &UNICODE
+        }
+    }
+
+    // Escape codes to start and reset a color, or nothing without color
+    fn paint(&self, code: &'static str) -> (&'static str, &'static str) {
+        if self.color {
+            (code, "\x1b[0m")
+        } else {
+            ("", "")
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!(
+                "Unsupported color choice '{}'. Use 'auto', 'always' or 'never'",
+                s
+            )),
+        }
+    }
+}
+
+impl ColorChoice {
+    // Auto colors output to a terminal unless NO_COLOR is set to anything
+    // but an empty string (https://no-color.org)
+    pub fn enabled(self, is_terminal: bool) -> bool {
+        self.resolve(is_terminal, std::env::var_os("NO_COLOR"))
+    }
+
+    fn resolve(self, is_terminal: bool, no_color: Option<std::ffi::OsString>) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => is_terminal && no_color.map_or(true, |value| value.is_empty()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlyphChoice {
+    Auto,
+    Unicode,
+    Ascii,
+}
+
+impl FromStr for GlyphChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(GlyphChoice::Auto),
+            "unicode" => Ok(GlyphChoice::Unicode),
+            "ascii" => Ok(GlyphChoice::Ascii),
+            _ => Err(format!(
+                "Unsupported glyph choice '{}'. Use 'auto', 'unicode' or 'ascii'",
+                s
+            )),
+        }
+    }
+}
+
+impl GlyphChoice {
+    // Auto keeps to ASCII when the output goes to a file or another program,
+    // which may not expect box drawing characters
+    pub fn ascii(self, is_terminal: bool) -> bool {
+        match self {
+            GlyphChoice::Auto => !is_terminal,
+            GlyphChoice::Unicode => false,
+            GlyphChoice::Ascii => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CdfOptions {
+    // Percentiles (0.0 to 1.0) to show a row for
+    pub percentiles: Vec<f64>,
+    pub bar_width: usize,
+    // Lower and upper percentiles of the annotated interval
+    pub interval: (f64, f64),
+    pub style: Style,
+    // Leaves out the calendar days column and shortens the annotation
+    pub compact: bool,
+    pub unit: DisplayUnit,
+}
+
+impl Default for CdfOptions {
+    fn default() -> Self {
+        CdfOptions {
+            percentiles: percentile_steps(0.05),
+            bar_width: 60,
+            interval: (0.05, 0.95),
+            style: Style::default(),
+            compact: false,
+            unit: DisplayUnit::default(),
+        }
+    }
+}
+
+impl CdfOptions {
+    // Picks the layout and sizes the bar so the chart fills a terminal
+    // `columns` wide
+    pub fn fit_width(&mut self, columns: usize) {
+        self.compact = columns < COMPACT_BELOW;
+        let fixed = if self.compact {
+            COMPACT_FIXED_COLUMNS
+        } else {
+            FIXED_COLUMNS
+        };
+        let used = self.label_width() + fixed;
+        self.bar_width = columns
+            .saturating_sub(used)
+            .clamp(MIN_BAR_WIDTH, MAX_BAR_WIDTH);
+    }
+
+    // Row percentiles, highest first, always including the interval bounds
+    fn rows(&self) -> Vec<f64> {
+        let mut rows = self.percentiles.clone();
+        rows.extend([self.interval.0, self.interval.1]);
+        rows.sort_by(|a, b| b.total_cmp(a));
+        rows.dedup_by(|a, b| (*a - *b).abs() < 1e-9);
+        rows
+    }
+
+    fn label_width(&self) -> usize {
+        self.rows()
+            .iter()
+            .map(|&p| percent_label(p).len() + 1)
+            .max()
+            .unwrap_or(0)
+            .max(4)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistogramOptions {
+    pub bins: usize,
+    pub bar_width: usize,
+    pub style: Style,
+    pub unit: DisplayUnit,
+}
+
+impl Default for HistogramOptions {
+    fn default() -> Self {
+        HistogramOptions {
+            bins: 20,
+            bar_width: 60,
+            style: Style::default(),
+            unit: DisplayUnit::default(),
+        }
+    }
+}
+
+impl HistogramOptions {
+    // Sizes the bars so the chart fills a terminal `columns` wide
+    pub fn fit_width(&mut self, columns: usize) {
+        self.bar_width = columns
+            .saturating_sub(HISTOGRAM_FIXED_COLUMNS)
+            .clamp(MIN_BAR_WIDTH, MAX_BAR_WIDTH);
+    }
+}
+
+// Percentiles every `step` (0.0 to 1.0) from 100% down to 0%
+pub fn percentile_steps(step: f64) -> Vec<f64> {
+    let mut steps: Vec<f64> = (0..)
+        .map(|i| 1.0 - i as f64 * step)
+        .take_while(|&p| p > 1e-9)
+        .collect();
+    steps.push(0.0);
+    steps
+}
+
+// A percentile as a percentage, without trailing zeros, e.g. 97.5
+fn percent_label(p: f64) -> String {
+    format!("{}", (p * 100_000.0).round() / 1000.0)
+}
+
+fn days(duration: Duration) -> f64 {
+    duration.as_secs_f64() / 86400.0
+}
+
+// Pads `text` on both sides to `width`, truncating text that doesn't fit
+fn centered(text: &str, width: usize) -> String {
+    let text: String = text.chars().take(width).collect();
+    let length = text.chars().count();
+    let padding = (width - length) / 2;
+    format!(
+        "{:padding_left$}{}{:padding_right$}",
+        "",
+        text,
+        "",
+        padding_left = padding,
+        padding_right = width - padding - length
+    )
+}
+
+fn color_code(p: f64) -> &'static str {
+    match (p * 100.0).round() as u32 {
+        0..=49 => "\x1b[31m",        // Red
+        50..=69 => "\x1b[38;5;173m", // Orange
+        70..=84 => "\x1b[33m",       // Yellow
+        85..=95 => "\x1b[32m",       // Green
+        _ => "\x1b[33m",             // Yellow
+    }
+}
+
+// Marks for the right of each row, bracketing the interval between the rows
+// at `top` and `bottom` and labelling it with its width. Compact marks only
+// label the top of the bracket.
+fn annotations(
+    rows: usize,
+    (top, bottom): (usize, usize),
+    width: f64,
+    glyphs: &Glyphs,
+    compact: bool,
+) -> Vec<String> {
+    let g = glyphs;
+    let label = format!("{}%", percent_label(width));
+    let inside = bottom - top - 1;
+    let mut marks = vec![String::new(); rows];
+    marks[top] = format!("{}{}{}", g.arrow, g.heavy, g.corners[0]);
+    for mark in &mut marks[top + 1..bottom] {
+        *mark = format!("  {}", g.side);
+    }
+    marks[bottom] = format!("{}{}{}", g.arrow, g.heavy, g.corners[1]);
+    if compact {
+        marks[top].push_str(&format!(" {}", label));
+    } else if inside >= 5 {
+        let first = top + 1 + (inside - 5) / 2;
+        let line = g.heavy.repeat(12);
+        marks[first] = format!("  {}{}{}", g.tee, line, g.corners[0]);
+        marks[first + 1] = format!("  {} {:<10} {}", g.side, label, g.side);
+        marks[first + 2] = format!("  {} Confidence {}", g.side, g.side);
+        marks[first + 3] = format!("  {} Interval   {}", g.side, g.side);
+        marks[first + 4] = format!("  {}{}{}", g.tee, line, g.corners[1]);
+    } else {
+        marks[top].push_str(&format!(" {} interval", label));
+    }
+    marks
+}
+
+// Draws the distribution of `data` as a table with a row per percentile. Each
+// row's bar marks the midpoint of the durations between its percentile and the
+// next lower one, which is also shown in workdays, in calendar days from
+// `start` and as the date it finishes by.
+pub fn cdf(
+    data: &[Duration],
+    title: &str,
+    options: &CdfOptions,
+    start: NaiveDate,
+    calendar: &Calendar,
+) -> Result<String, String> {
+    let mut sorted = data.to_vec();
+    sorted.sort_unstable();
+    let min = days(sorted[0]);
+    let max = days(*sorted.last().unwrap());
+    let rows = options.rows();
+    let label_width = options.label_width();
+    let bar_width = options.bar_width;
+    let compact = options.compact;
+    let glyphs = options.style.glyphs();
+    let g = glyphs;
+
+    // Calculate bar positions
+    let mut bar_positions = Vec::new();
+    for (i, &upper) in rows.iter().enumerate() {
+        let lower = rows.get(i + 1).copied().unwrap_or(0.0);
+        let midpoint = (percentile(&sorted, lower) + percentile(&sorted, upper)) / 2;
+        let days = days(midpoint);
+        let normalized_position = if max > min {
+            (days - min) / (max - min)
+        } else {
+            0.0
+        };
+        let bar_position = (normalized_position * bar_width as f64).round() as usize;
+        bar_positions.push((bar_position, days));
+    }
+
+    // Determine the needed shift to center the graph
+    let min_bar_position = bar_positions.iter().map(|(pos, _)| *pos).min().unwrap_or(0);
+    let max_bar_position = bar_positions
+        .iter()
+        .map(|(pos, _)| *pos)
+        .max()
+        .unwrap_or(bar_width);
+    let offset = bar_width.saturating_sub(max_bar_position - min_bar_position) / 2;
+
+    let position = |p: f64| rows.iter().position(|&row| (row - p).abs() < 1e-9).unwrap();
+    let marks = annotations(
+        rows.len(),
+        (position(options.interval.1), position(options.interval.0)),
+        options.interval.1 - options.interval.0,
+        glyphs,
+        compact,
+    );
+
+    let columns = if compact { 2 } else { 3 };
+    let rule = |join: &str| {
+        format!(
+            "{}{}{}{}",
+            g.rule.repeat(label_width),
+            join,
+            g.rule.repeat(bar_width),
+            format!("{}{}", join, g.rule.repeat(10)).repeat(columns)
+        )
+    };
+    let d = g.divider;
+
+    let mut chart = String::new();
+    writeln!(chart, "{}", rule(g.joins[0])).unwrap();
+    write!(
+        chart,
+        "{:<label_width$}{d}{}{d}{}{d}",
+        "%ile",
+        centered(title, bar_width),
+        centered(options.unit.heading(), 10)
+    )
+    .unwrap();
+    if !compact {
+        write!(chart, " Schedule {d}").unwrap();
+    }
+    writeln!(chart, " Complete  ").unwrap();
+    writeln!(chart, "{}", rule(g.joins[1])).unwrap();
+
+    for (i, ((bar_position, days), &p)) in bar_positions.iter().zip(&rows).enumerate() {
+        let end_date = finish_date_after(calendar, start, days_to_duration(*days))?;
+        let calendar_duration = end_date.signed_duration_since(start);
+
+        let shifted_bar_position = bar_position - min_bar_position + offset;
+
+        let (fg, bg) = g.fills[i % 2];
+
+        let (color_code, reset_code) = options.style.paint(color_code(p));
+
+        let bar_with_divider: String = (0..bar_width)
+            .map(|j| match j {
+                _ if j == shifted_bar_position => g.marker,
+                _ if j < shifted_bar_position => fg,
+                _ => bg,
+            })
+            .collect();
+
+        write!(
+            chart,
+            "{}{:>label_width$}{}{d}{}{}{}{d}{}{:5.*} {:<4}{}{d}",
+            color_code,
+            format!("p{}", percent_label(p)),
+            reset_code,
+            color_code,
+            bar_with_divider,
+            reset_code,
+            color_code,
+            options.unit.decimals(),
+            options.unit.value(days_to_duration(*days)),
+            options.unit.abbreviation(),
+            reset_code,
+        )
+        .unwrap();
+        if !compact {
+            write!(
+                chart,
+                "{}{:5.0} days{}{d}",
+                color_code,
+                calendar_duration.num_days(),
+                reset_code,
+            )
+            .unwrap();
+        }
+        writeln!(
+            chart,
+            "{}{}{}{}",
+            color_code, end_date, reset_code, marks[i]
+        )
+        .unwrap();
+    }
+    writeln!(chart, "{}", rule(g.joins[2])).unwrap();
+
+    Ok(chart)
+}
+
+// A bar `length` characters long, padded to `width`
+fn horizontal_bar(length: f64, width: usize, glyphs: &Glyphs) -> String {
+    let bar = match glyphs.eighths {
+        Some(eighths) => {
+            let count = (length * 8.0).round() as usize;
+            let mut bar = glyphs.block.repeat(count / 8);
+            if count % 8 > 0 {
+                bar.push(eighths[count % 8]);
+            }
+            bar
+        }
+        None => glyphs.block.repeat(length.round() as usize),
+    };
+    format!("{:<width$}", bar)
+}
+
+// Draws the distribution of `data` with a row per bin of equal width, its bar
+// showing the share of outcomes in that bin. The rows holding the mean, the
+// median and the mode (the middle of the fullest bin) are marked.
+pub fn histogram(data: &[Duration], title: &str, options: &HistogramOptions) -> String {
+    let mut sorted = data.to_vec();
+    sorted.sort_unstable();
+    let bins = options.bins;
+    let bar_width = options.bar_width;
+    let g = options.style.glyphs();
+    let counts = bin_counts(&sorted, bins);
+    let value = |duration| options.unit.value(duration);
+    let min = value(sorted[0]);
+    let bin_width = (value(*sorted.last().unwrap()) - min) / bins as f64;
+    let fullest = (0..bins).fold(0, |best, i| if counts[i] > counts[best] { i } else { best });
+
+    let mean = value(Summary::new(&sorted).mean);
+    let median = value(percentile(&sorted, 0.5));
+    let mode = min + (fullest as f64 + 0.5) * bin_width;
+    let bin_of = |value: f64| {
+        if bin_width > 0.0 {
+            (((value - min) / bin_width) as usize).min(bins - 1)
+        } else {
+            0
+        }
+    };
+    let mut marks = vec![Vec::new(); bins];
+    marks[bin_of(mean)].push("mean");
+    marks[bin_of(median)].push("median");
+    marks[fullest].push("mode");
+
+    let rule = |join: &str| {
+        format!(
+            "{}{join}{}{join}{}",
+            g.rule.repeat(RANGE_WIDTH),
+            g.rule.repeat(bar_width),
+            g.rule.repeat(SHARE_WIDTH)
+        )
+    };
+    let d = g.divider;
+
+    let mut chart = String::new();
+    writeln!(chart, "{}", rule(g.joins[0])).unwrap();
+    writeln!(
+        chart,
+        "{}{d}{}{d} Share  ",
+        centered(options.unit.heading(), RANGE_WIDTH),
+        centered(title, bar_width)
+    )
+    .unwrap();
+    writeln!(chart, "{}", rule(g.joins[1])).unwrap();
+
+    let top = counts[fullest].max(1) as f64;
+    for (i, &count) in counts.iter().enumerate() {
+        let low = min + i as f64 * bin_width;
+        let mark = if marks[i].is_empty() {
+            String::new()
+        } else {
+            format!("  {} {}", g.arrow, marks[i].join(", "))
+        };
+        writeln!(
+            chart,
+            " {:>6.1} - {:<6.1}{d}{}{d} {:5.1}%{}",
+            low,
+            low + bin_width,
+            horizontal_bar(count as f64 / top * bar_width as f64, bar_width, g),
+            count as f64 / sorted.len() as f64 * 100.0,
+            mark
+        )
+        .unwrap();
+    }
+    writeln!(chart, "{}", rule(g.joins[2])).unwrap();
+    writeln!(
+        chart,
+        "Mean {:.1}, median {:.1}, mode {:.1} {}",
+        mean,
+        median,
+        mode,
+        options.unit.name()
+    )
+    .unwrap();
+
+    chart
+}
+
+// Summary statistics of `data` and, given the single-point `plan`, how much
+// longer the simulated p50 and p85 are
+pub fn summary(data: &[Duration], plan: Option<Duration>, unit: DisplayUnit) -> String {
+    let stats = Summary::new(data);
+    let mut text = format!(
+        "Mean {:.1}, standard deviation {:.1} (CV {:.0}%), skewness {:.2}, min {:.1}, max {:.1} {}\n",
+        unit.value(stats.mean),
+        unit.value(stats.std_dev),
+        stats.coefficient_of_variation() * 100.0,
+        stats.skewness,
+        unit.value(stats.min),
+        unit.value(stats.max),
+        unit.name()
+    );
+    if let Some(plan) = plan {
+        let mut sorted = data.to_vec();
+        sorted.sort_unstable();
+        let longer = |p: f64| (days(percentile(&sorted, p)) / days(plan) - 1.0) * 100.0;
+        writeln!(
+            text,
+            "Sum of likely estimates along the critical path: {:.1} {} (p50 {:+.0}%, p85 {:+.0}%)",
+            unit.value(plan),
+            unit.name(),
+            longer(0.5),
+            longer(0.85)
+        )
+        .unwrap();
+    }
+    text
+}
+
+// How many iterations ran to reach `precision`, and the 95% confidence band
+// and standard error of each key completion time percentile
+pub fn confidence_bands(
+    convergence: &Convergence,
+    precision: Duration,
+    unit: DisplayUnit,
+) -> String {
+    let name = unit.name();
+    let mut text = if convergence.converged {
+        format!(
+            "Converged to within ±{:.2} {} after {} iterations.\n",
+            unit.value(precision),
+            name,
+            convergence.iterations
+        )
+    } else {
+        format!(
+            "Stopped after {} iterations without converging to within ±{:.2} {}.\n",
+            convergence.iterations,
+            unit.value(precision),
+            name
+        )
+    };
+    for band in &convergence.bands {
+        writeln!(
+            text,
+            "{:>5} {:7.2} {} ±{:.2} (95% band {:.2} to {:.2}, standard error {:.3})",
+            format!("p{}", percent_label(band.percentile)),
+            unit.value(band.value),
+            name,
+            unit.value(band.half_width()),
+            unit.value(band.lower),
+            unit.value(band.upper),
+            unit.value(band.standard_error())
+        )
+        .unwrap();
+    }
+    text
+}
+
+// Chance of each task missing its `must_finish_by` deadline
+pub fn deadlines(forecasts: &[DeadlineForecast]) -> String {
+    let mut text = String::from("Deadlines:\n");
+    for forecast in forecasts {
+        writeln!(
+            text,
+            "  {} must finish by {}: {:.0}% chance of missing it",
+            forecast.name,
+            forecast.deadline,
+            forecast.miss_chance * 100.0
+        )
+        .unwrap();
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{convergence::PercentileBand, units::Unit};
+
+    fn sample() -> Vec<Duration> {
+        (0..=100)
+            .map(|i| days_to_duration(10.0 + i as f64 / 10.0))
+            .collect()
+    }
+
+    fn monday() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 8, 5).unwrap()
+    }
+
+    #[test]
+    fn test_percentile_steps() {
+        let steps = percentile_steps(0.25);
+        assert_eq!(steps, vec![1.0, 0.75, 0.5, 0.25, 0.0]);
+        assert_eq!(percentile_steps(0.05).len(), 21);
+        assert_eq!(percentile_steps(0.3).last(), Some(&0.0));
+        assert_eq!(percent_label(percentile_steps(0.025)[1]), "97.5");
+    }
+
+    #[test]
+    fn test_rows_include_interval() {
+        let options = CdfOptions {
+            percentiles: vec![0.5, 0.9, 0.1],
+            interval: (0.2, 0.9),
+            ..Default::default()
+        };
+        assert_eq!(options.rows(), vec![0.9, 0.5, 0.2, 0.1]);
+    }
+
+    #[test]
+    fn test_fit_width() {
+        let mut options = CdfOptions::default();
+        options.fit_width(114);
+        assert_eq!(options.bar_width, 60);
+        options.fit_width(40);
+        assert_eq!(options.bar_width, MIN_BAR_WIDTH);
+        options.fit_width(1000);
+        assert_eq!(options.bar_width, MAX_BAR_WIDTH);
+    }
+
+    #[test]
+    fn test_default_chart() {
+        let chart = cdf(
+            &sample(),
+            "Title",
+            &CdfOptions::default(),
+            monday(),
+            &Calendar::new(),
+        )
+        .unwrap();
+        let lines: Vec<_> = chart.lines().collect();
+
+        // Header, 21 rows and a footer
+        assert_eq!(lines.len(), 25);
+        assert!(lines[1].starts_with("%ile│"));
+        assert!(lines[3].contains("p100"));
+        assert!(lines[4].ends_with("◀━┓"));
+        assert!(lines[12].ends_with("┃ 90%        ┃"));
+        assert!(lines[22].ends_with("◀━┛"));
+        assert!(lines[23].contains("p0"));
+    }
+
+    #[test]
+    fn test_dates_count_partial_days() {
+        let data = vec![days_to_duration(2.5); 10];
+        let chart = cdf(
+            &data,
+            "Title",
+            &CdfOptions::default(),
+            monday(),
+            &Calendar::new(),
+        )
+        .unwrap();
+        let lines: Vec<_> = chart.lines().collect();
+
+        // Half way through the third working day, not done by the second
+        assert!(lines[3].contains("2 days│2024-08-07"));
+    }
+
+    #[test]
+    fn test_custom_percentiles() {
+        let options = CdfOptions {
+            percentiles: vec![0.99, 0.95, 0.9, 0.8, 0.5, 0.1],
+            bar_width: 30,
+            interval: (0.1, 0.9),
+            ..Default::default()
+        };
+        let chart = cdf(&sample(), "Title", &options, monday(), &Calendar::new()).unwrap();
+        let lines: Vec<_> = chart.lines().collect();
+
+        assert_eq!(lines.len(), 10);
+        // Too few rows between the bounds for the boxed label
+        assert!(lines[5].ends_with("◀━┓ 80% interval"));
+        assert!(lines[8].ends_with("◀━┛"));
+        // Midway between the p10 and p50 durations
+        assert!(lines[7].contains("   13 days"));
+    }
+
+    #[test]
+    fn test_horizontal_bar() {
+        assert_eq!(horizontal_bar(2.5, 4, &UNICODE), "██▌ ");
+        assert_eq!(horizontal_bar(0.0, 2, &UNICODE), "  ");
+        assert_eq!(horizontal_bar(3.0, 3, &UNICODE), "███");
+        assert_eq!(horizontal_bar(2.4, 4, &ASCII), "##  ");
+    }
+
+    #[test]
+    fn test_histogram() {
+        let data: Vec<_> = [1.0, 1.1, 2.0, 2.1, 2.2, 3.0]
+            .iter()
+            .map(|&d| days_to_duration(d))
+            .collect();
+        let options = HistogramOptions {
+            bins: 4,
+            bar_width: 8,
+            ..Default::default()
+        };
+        let chart = histogram(&data, "Title", &options);
+        let lines: Vec<_> = chart.lines().collect();
+
+        // Header, 4 bins, a footer and the summary
+        assert_eq!(lines.len(), 9);
+        assert_eq!(lines[3], "    1.0 - 1.5   │█████▍  │  33.3%");
+        // The mean can fall in an empty bin
+        assert_eq!(lines[4], "    1.5 - 2.0   │        │   0.0%  ◀ mean");
+        assert_eq!(
+            lines[5],
+            "    2.0 - 2.5   │████████│  50.0%  ◀ median, mode"
+        );
+        assert_eq!(lines[6], "    2.5 - 3.0   │██▋     │  16.7%");
+        assert!(lines[8].starts_with("Mean 1.9, median 2.1, mode "));
+    }
+
+    #[test]
+    fn test_color_choice() {
+        assert!(ColorChoice::Auto.resolve(true, None));
+        assert!(!ColorChoice::Auto.resolve(false, None));
+        assert!(!ColorChoice::Auto.resolve(true, Some("1".into())));
+        // An empty NO_COLOR doesn't count
+        assert!(ColorChoice::Auto.resolve(true, Some("".into())));
+        assert!(ColorChoice::Always.resolve(false, Some("1".into())));
+        assert!(!ColorChoice::Never.resolve(true, None));
+        assert_eq!("ALWAYS".parse::<ColorChoice>(), Ok(ColorChoice::Always));
+        assert!("sometimes".parse::<ColorChoice>().is_err());
+    }
+
+    #[test]
+    fn test_glyph_choice() {
+        // Piped output is ASCII by default
+        assert!(GlyphChoice::Auto.ascii(false));
+        assert!(!GlyphChoice::Auto.ascii(true));
+        assert!(!GlyphChoice::Unicode.ascii(false));
+        assert!(GlyphChoice::Ascii.ascii(true));
+        assert_eq!("Unicode".parse::<GlyphChoice>(), Ok(GlyphChoice::Unicode));
+        assert!("emoji".parse::<GlyphChoice>().is_err());
+    }
+
+    #[test]
+    fn test_styles() {
+        let calendar = Calendar::new();
+        let mut options = CdfOptions::default();
+        let chart = cdf(&sample(), "Title", &options, monday(), &calendar).unwrap();
+        assert!(!chart.contains('\x1b'));
+
+        options.style.color = true;
+        let chart = cdf(&sample(), "Title", &options, monday(), &calendar).unwrap();
+        assert!(chart.contains("\x1b[32m p95\x1b[0m│"));
+
+        options.style = Style {
+            color: false,
+            ascii: true,
+        };
+        let chart = cdf(&sample(), "Title", &options, monday(), &calendar).unwrap();
+        assert!(chart.is_ascii());
+        assert!(chart.contains("| Interval   |"));
+        let histogram_options = HistogramOptions {
+            style: options.style,
+            ..Default::default()
+        };
+        assert!(histogram(&sample(), "Title", &histogram_options).is_ascii());
+    }
+
+    #[test]
+    fn test_compact_layout() {
+        let mut options = CdfOptions::default();
+        options.fit_width(80);
+        assert!(options.compact);
+        let chart = cdf(&sample(), "Title", &options, monday(), &Calendar::new()).unwrap();
+        let lines: Vec<_> = chart.lines().collect();
+
+        assert!(!lines[1].contains("Schedule"));
+        assert!(lines[4].ends_with("◀━┓ 90%"));
+        assert!(lines.iter().all(|line| line.chars().count() <= 80));
+
+        options.fit_width(100);
+        assert!(!options.compact);
+    }
+
+    #[test]
+    fn test_summary() {
+        let text = summary(
+            &sample(),
+            Some(days_to_duration(12.0)),
+            DisplayUnit::default(),
+        );
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(
+            lines[0],
+            "Mean 15.0, standard deviation 2.9 (CV 19%), skewness 0.00, min 10.0, max 20.0 workdays"
+        );
+        assert_eq!(
+            lines[1],
+            "Sum of likely estimates along the critical path: 12.0 workdays (p50 +25%, p85 +54%)"
+        );
+        assert_eq!(
+            summary(&sample(), None, DisplayUnit::default())
+                .lines()
+                .count(),
+            1
+        );
+        let hours = DisplayUnit::new(Unit::Hours, &Calendar::new()).unwrap();
+        assert_eq!(
+            summary(&sample(), None, hours),
+            "Mean 120.0, standard deviation 23.3 (CV 19%), skewness 0.00, min 80.0, max 160.0 hours\n"
+        );
+    }
+
+    #[test]
+    fn test_confidence_bands() {
+        let sorted = sample();
+        let convergence = Convergence {
+            iterations: sorted.len(),
+            converged: false,
+            bands: vec![PercentileBand::new(&sorted, 0.5)],
+        };
+        let text = confidence_bands(&convergence, days_to_duration(0.5), DisplayUnit::default());
+        let lines: Vec<_> = text.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            "Stopped after 101 iterations without converging to within ±0.50 workdays."
+        );
+        assert!(lines[1].starts_with("  p50   15.00 workdays ±1.05 (95% band 14.00 to 16.10"));
+    }
+
+    #[test]
+    fn test_deadlines() {
+        let forecasts = vec![DeadlineForecast {
+            name: "Launch".to_string(),
+            deadline: monday(),
+            miss_chance: 0.234,
+        }];
+        assert_eq!(
+            deadlines(&forecasts),
+            "Deadlines:\n  Launch must finish by 2024-08-05: 23% chance of missing it\n"
+        );
+    }
+}
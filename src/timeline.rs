@@ -0,0 +1,448 @@
+// Calendar time, which the simulation advances through. Instants are seconds
+// since midnight at the start of the project, and a worker gets through one
+// workday of work (86,400 seconds) in each day they're fully available.
+// Results are reported as working time on the project calendar, which maps
+// back to the same dates.
+use crate::{
+    calendar::Calendar,
+    schedule::{Project, Worker},
+};
+
+use chrono::{Datelike, Days, NaiveDate, Utc};
+
+use std::time::Duration;
+
+const DAY: f64 = 86400.0;
+
+// Fewest days worked out in advance; any later ones are worked out as needed
+const HORIZON_DAYS: usize = 3660;
+
+// Days in a row without working time after which there's taken to be none
+const GIVE_UP_DAYS: usize = 5 * 366;
+
+#[derive(Debug, Clone)]
+struct Availability {
+    // Works whenever the project calendar does, so work can be counted in
+    // project working time
+    follows_calendar: bool,
+    allocation: f64,
+    // Indexed by days from Monday
+    work_days: [bool; 7],
+    time_off: Vec<(NaiveDate, NaiveDate)>,
+    // Share of each day from the start spent on the project
+    rates: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    start: NaiveDate,
+    calendar: Calendar,
+    // Working time on the project calendar before each day, in workdays
+    working_days_before: Vec<f64>,
+    workers: Vec<Availability>,
+}
+
+impl Timeline {
+    // Starts on the project's start date, or today when it has none. Without
+    // named workers, every worker works whenever the project calendar does.
+    pub fn new(project: &Project) -> Self {
+        let start = project
+            .start_date
+            .unwrap_or_else(|| Utc::now().date_naive());
+        let mut timeline = Timeline {
+            start,
+            calendar: project.calendar.clone(),
+            working_days_before: Vec::new(),
+            workers: Vec::new(),
+        };
+
+        // Enough days for the whole project with everyone busy, with room to
+        // spare, as days past them are slow to work out
+        let busy_days = project
+            .tasks
+            .iter()
+            .map(|task| task.max_time.as_secs_f64() / DAY)
+            .sum::<f64>()
+            / project.num_workers.max(1) as f64;
+        let horizon = HORIZON_DAYS.max((busy_days * 2.0) as usize);
+        let mut working_days = 0.0;
+        for day in 0..horizon {
+            timeline.working_days_before.push(working_days);
+            working_days += timeline.day_length(day);
+        }
+
+        let anonymous = Worker::new("");
+        let workers: Vec<_> = if project.workers.is_empty() {
+            vec![&anonymous; project.num_workers]
+        } else {
+            project.workers.iter().collect()
+        };
+        for worker in workers {
+            let mut work_days = [false; 7];
+            for (i, works) in work_days.iter_mut().enumerate() {
+                let day = chrono::Weekday::try_from(i as u8).unwrap();
+                *works = match &worker.work_days {
+                    Some(days) => days.contains(&day),
+                    None => timeline.calendar.is_work_day(day),
+                };
+            }
+            let mut availability = Availability {
+                follows_calendar: worker.is_always_available(),
+                allocation: worker.allocation,
+                work_days,
+                time_off: worker.time_off.clone(),
+                rates: Vec::new(),
+            };
+            availability.rates = (0..horizon)
+                .map(|day| timeline.work_rate(&availability, day))
+                .collect();
+            timeline.workers.push(availability);
+        }
+        timeline
+    }
+
+    pub fn date(&self, day: usize) -> NaiveDate {
+        self.start + Days::new(day as u64)
+    }
+
+    // Share of `day` worked on the project calendar
+    fn day_length(&self, day: usize) -> f64 {
+        self.calendar.day_length(self.date(day))
+    }
+
+    fn work_rate(&self, availability: &Availability, day: usize) -> f64 {
+        let date = self.date(day);
+        let works = availability.work_days[date.weekday().num_days_from_monday() as usize]
+            && !self.calendar.is_holiday(date)
+            && !availability
+                .time_off
+                .iter()
+                .any(|&(first, last)| first <= date && date <= last);
+        if works && self.calendar.is_half_day(date) {
+            availability.allocation / 2.0
+        } else if works {
+            availability.allocation
+        } else {
+            0.0
+        }
+    }
+
+    // Share of `day` that `worker` spends on the project
+    fn rate(&self, worker: usize, day: usize) -> f64 {
+        let availability = &self.workers[worker];
+        match availability.rates.get(day) {
+            Some(&rate) => rate,
+            None => self.work_rate(availability, day),
+        }
+    }
+
+    fn no_working_time(&self, day: usize) -> String {
+        format!("No working time after {}", self.date(day))
+    }
+
+    // When `worker` is next able to work, from `time`
+    pub fn next_available(&self, worker: usize, time: Duration) -> Result<Duration, String> {
+        let today = (time.as_secs_f64() / DAY) as usize;
+        let mut day = today;
+        while self.rate(worker, day) == 0.0 {
+            if day - today >= GIVE_UP_DAYS {
+                return Err(self.no_working_time(today));
+            }
+            day += 1;
+        }
+        Ok(if day == today {
+            time
+        } else {
+            Duration::from_secs_f64(day as f64 * DAY)
+        })
+    }
+
+    // When `worker`, starting at `time`, finishes `work` workdays of work
+    pub fn finish(
+        &self,
+        worker: usize,
+        time: Duration,
+        work: Duration,
+    ) -> Result<Duration, String> {
+        if self.workers[worker].follows_calendar {
+            return self.time_at(self.workdays_at(time) + work);
+        }
+        let mut time = time.as_secs_f64();
+        let mut remaining = work.as_secs_f64();
+        let mut idle_days = 0;
+        loop {
+            let day = (time / DAY) as usize;
+            let end_of_day = (day + 1) as f64 * DAY;
+            let rate = self.rate(worker, day);
+            if rate > 0.0 {
+                let possible = (end_of_day - time) * rate;
+                if remaining <= possible {
+                    return Ok(Duration::from_secs_f64(time + remaining / rate));
+                }
+                remaining -= possible;
+                idle_days = 0;
+            } else if idle_days >= GIVE_UP_DAYS {
+                return Err(self.no_working_time(day - idle_days));
+            } else {
+                idle_days += 1;
+            }
+            time = end_of_day;
+        }
+    }
+
+    // When `workdays` of working time on the project calendar have passed,
+    // ending a day rather than starting the next on whole days
+    pub fn time_at(&self, workdays: Duration) -> Result<Duration, String> {
+        let workdays = workdays.as_secs_f64() / DAY;
+        let after = self
+            .working_days_before
+            .partition_point(|&before| before < workdays);
+        // Past the days worked out in advance, count on from the last of them
+        let (mut day, mut before) = match after {
+            0 => (0, 0.0),
+            _ => (after - 1, self.working_days_before[after - 1]),
+        };
+        // Counting any days without working time just before
+        let mut idle_days = day
+            - self
+                .working_days_before
+                .partition_point(|&days| days < before);
+        loop {
+            let length = self.day_length(day);
+            if length > 0.0 && before + length >= workdays {
+                let into_day = (workdays - before) / length * DAY;
+                return Ok(Duration::from_secs_f64(day as f64 * DAY + into_day));
+            }
+            if length > 0.0 {
+                idle_days = 0;
+            } else if idle_days >= GIVE_UP_DAYS {
+                return Err(self.no_working_time(day - idle_days));
+            } else {
+                idle_days += 1;
+            }
+            before += length;
+            day += 1;
+        }
+    }
+
+    // The start of `date`, or of the project if that's later
+    pub fn time_of(&self, date: NaiveDate) -> Duration {
+        let days = date.signed_duration_since(self.start).num_days().max(0);
+        Duration::from_secs(days as u64 * DAY as u64)
+    }
+
+    // Working time on the project calendar up to the end of `date`
+    pub fn workdays_by(&self, date: NaiveDate) -> Duration {
+        if date < self.start {
+            return Duration::ZERO;
+        }
+        self.workdays_at(self.time_of(date) + Duration::from_secs(DAY as u64))
+    }
+
+    // Working time on the project calendar that has passed by `time`
+    pub fn workdays_at(&self, time: Duration) -> Duration {
+        let time = time.as_secs_f64();
+        let day = (time / DAY) as usize;
+        let before = match self.working_days_before.get(day) {
+            Some(&days) => days,
+            None => {
+                let last = self.working_days_before.len() - 1;
+                self.working_days_before[last]
+                    + (last..day).map(|d| self.day_length(d)).sum::<f64>()
+            }
+        };
+        let today = (time - day as f64 * DAY) * self.day_length(day);
+        Duration::from_secs_f64(before * DAY + today)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{days_to_duration, Task};
+
+    fn project(workers: Vec<Worker>) -> Project {
+        let task = Task::new(
+            "A",
+            vec![],
+            days_to_duration(1.0),
+            days_to_duration(2.0),
+            days_to_duration(3.0),
+        );
+        let mut project = Project::new(vec![task], workers.len(), None).unwrap();
+        // A Monday
+        project.start_date = NaiveDate::from_ymd_opt(2026, 11, 2);
+        project.workers = workers;
+        project
+    }
+
+    #[test]
+    fn test_full_time_worker_follows_project_calendar() {
+        let timeline = Timeline::new(&project(vec![Worker::new("Ann")]));
+
+        // Half of Friday, then half of Monday
+        let friday_noon = days_to_duration(4.5);
+        let finish = timeline
+            .finish(0, friday_noon, days_to_duration(1.0))
+            .unwrap();
+        assert_eq!(finish, days_to_duration(7.5));
+        assert_eq!(timeline.workdays_at(finish), days_to_duration(5.5));
+        // Weekends don't count as working time
+        assert_eq!(
+            timeline.workdays_at(days_to_duration(6.0)),
+            days_to_duration(5.0)
+        );
+        assert_eq!(
+            timeline.next_available(0, days_to_duration(5.0)).unwrap(),
+            days_to_duration(7.0)
+        );
+        // A whole week of work ends on Friday evening
+        assert_eq!(
+            timeline
+                .finish(0, Duration::ZERO, days_to_duration(5.0))
+                .unwrap(),
+            days_to_duration(5.0)
+        );
+    }
+
+    #[test]
+    fn test_holidays_apply_to_everyone() {
+        let mut project = project(vec![Worker::new("Ann"), Worker::new("Bo")]);
+        project.workers[1].allocation = 0.5;
+        let wednesday = NaiveDate::from_ymd_opt(2026, 11, 4).unwrap();
+        project.calendar.add_holiday(wednesday);
+        let timeline = Timeline::new(&project);
+
+        // Monday, Tuesday and Thursday
+        let finish = timeline
+            .finish(0, Duration::ZERO, days_to_duration(3.0))
+            .unwrap();
+        assert_eq!(finish, days_to_duration(4.0));
+        assert_eq!(timeline.workdays_at(finish), days_to_duration(3.0));
+        // Half days on Monday to Friday, but for Wednesday
+        let finish = timeline
+            .finish(1, Duration::ZERO, days_to_duration(2.0))
+            .unwrap();
+        assert_eq!(finish, days_to_duration(5.0));
+        assert_eq!(timeline.workdays_at(finish), days_to_duration(4.0));
+    }
+
+    #[test]
+    fn test_dates_and_working_time() {
+        let timeline = Timeline::new(&project(vec![Worker::new("Ann")]));
+        let date = |d| NaiveDate::from_ymd_opt(2026, 11, d).unwrap();
+
+        assert_eq!(timeline.time_of(date(4)), days_to_duration(2.0));
+        assert_eq!(timeline.time_of(date(1)), Duration::ZERO);
+        // Through Friday, and still through Friday at the end of Sunday
+        assert_eq!(timeline.workdays_by(date(6)), days_to_duration(5.0));
+        assert_eq!(timeline.workdays_by(date(8)), days_to_duration(5.0));
+        assert_eq!(timeline.workdays_by(date(1)), Duration::ZERO);
+        // Whole days end on the last working day, partial ones carry on after
+        // the weekend
+        assert_eq!(
+            timeline.time_at(days_to_duration(5.0)).unwrap(),
+            days_to_duration(5.0)
+        );
+        assert_eq!(
+            timeline.time_at(days_to_duration(5.25)).unwrap(),
+            days_to_duration(7.25)
+        );
+        assert_eq!(timeline.time_at(Duration::ZERO).unwrap(), Duration::ZERO);
+        // Far beyond the days worked out in advance
+        let later = days_to_duration(3000.5);
+        assert_eq!(
+            timeline.workdays_at(timeline.time_at(later).unwrap()),
+            later
+        );
+    }
+
+    #[test]
+    fn test_long_projects_are_worked_out_in_advance() {
+        let mut project = project(vec![Worker::new("Ann")]);
+        project.tasks[0].max_time = days_to_duration(10_000.0);
+        let timeline = Timeline::new(&project);
+
+        // Twice the days the work could take, rather than the usual ten years
+        assert_eq!(timeline.working_days_before.len(), 20_000);
+        let later = days_to_duration(10_000.5);
+        assert_eq!(
+            timeline.workdays_at(timeline.time_at(later).unwrap()),
+            later
+        );
+    }
+
+    #[test]
+    fn test_half_days() {
+        let mut project = project(vec![Worker::new("Ann"), Worker::new("Bo")]);
+        project.workers[1].work_days = Some(vec![chrono::Weekday::Tue, chrono::Weekday::Sat]);
+        let tuesday = NaiveDate::from_ymd_opt(2026, 11, 3).unwrap();
+        project.calendar.add_half_day(tuesday);
+        let timeline = Timeline::new(&project);
+
+        // Monday, then Tuesday morning counts for half a day
+        assert_eq!(
+            timeline.workdays_at(days_to_duration(2.0)),
+            days_to_duration(1.5)
+        );
+        assert_eq!(
+            timeline.time_at(days_to_duration(1.25)).unwrap(),
+            days_to_duration(1.5)
+        );
+        let finish = timeline
+            .finish(0, Duration::ZERO, days_to_duration(2.0))
+            .unwrap();
+        assert_eq!(finish, days_to_duration(2.5));
+        assert_eq!(timeline.workdays_at(finish), days_to_duration(2.0));
+        // Half of Tuesday, then all of Saturday
+        let finish = timeline
+            .finish(1, Duration::ZERO, days_to_duration(1.0))
+            .unwrap();
+        assert_eq!(finish, days_to_duration(5.5));
+    }
+
+    #[test]
+    fn test_part_time_worker_with_time_off() {
+        let mut worker = Worker::new("Bo");
+        worker.allocation = 0.5;
+        worker.work_days = Some(vec![chrono::Weekday::Mon, chrono::Weekday::Tue]);
+        let tuesday = NaiveDate::from_ymd_opt(2026, 11, 3).unwrap();
+        worker.time_off = vec![(tuesday, tuesday)];
+        let timeline = Timeline::new(&project(vec![worker]));
+
+        // Half of Monday, none of Tuesday (away), half of the next Monday
+        let finish = timeline
+            .finish(0, Duration::ZERO, days_to_duration(1.0))
+            .unwrap();
+        assert_eq!(finish, days_to_duration(8.0));
+        assert_eq!(
+            timeline.next_available(0, days_to_duration(1.0)).unwrap(),
+            days_to_duration(7.0)
+        );
+    }
+
+    #[test]
+    fn test_no_working_time_is_an_error() {
+        let mut idle = project(vec![Worker::new("Ann")]);
+        idle.calendar.set_work_days(&[]);
+        let timeline = Timeline::new(&idle);
+
+        let error = "No working time after 2026-11-02";
+        assert_eq!(
+            timeline.next_available(0, Duration::ZERO),
+            Err(error.into())
+        );
+        assert_eq!(timeline.time_at(days_to_duration(1.0)), Err(error.into()));
+
+        // Years away with no end in sight
+        let mut worker = Worker::new("Bo");
+        worker.work_days = Some(vec![chrono::Weekday::Mon]);
+        let monday = NaiveDate::from_ymd_opt(2026, 11, 2).unwrap();
+        worker.time_off = vec![(monday, monday + chrono::Days::new(10 * 366))];
+        let timeline = Timeline::new(&project(vec![worker]));
+        assert_eq!(
+            timeline.finish(0, Duration::ZERO, days_to_duration(1.0)),
+            Err(error.into())
+        );
+    }
+}
@@ -0,0 +1,463 @@
+// Issue tracker (Jira, Linear) CSV import
+use crate::{
+    calendar::Calendar,
+    schedule::Project,
+    schedule_loader::{EstimateInput, ScheduleInput, TaskInput},
+};
+
+use serde::Deserialize;
+
+use std::{collections::HashSet, io::Read, path::Path, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tracker {
+    Jira,
+    Linear,
+}
+
+impl FromStr for Tracker {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "jira" => Ok(Tracker::Jira),
+            "linear" => Ok(Tracker::Linear),
+            _ => Err(format!(
+                "Unsupported tracker '{}'. Use 'jira' or 'linear'",
+                s
+            )),
+        }
+    }
+}
+
+// CSV header of each field, matched case-insensitively. Jira repeats a header
+// once per value (e.g. one `Labels` column per label), so every column with a
+// matching header is read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackerColumns {
+    pub key: String,
+    pub summary: String,
+    pub description: String,
+    pub assignee: String,
+    pub labels: String,
+    // Issues this issue blocks, which become its dependents
+    pub blocks: String,
+    // Issues blocking this one, which become its dependencies
+    pub blocked_by: String,
+    pub points: String,
+    // Optional column holding a three-point estimate in days, e.g. `2/3/5`
+    pub three_point: Option<String>,
+}
+
+impl TrackerColumns {
+    pub fn for_tracker(tracker: Tracker) -> Self {
+        let columns = match tracker {
+            Tracker::Jira => [
+                "Issue key",
+                "Summary",
+                "Description",
+                "Assignee",
+                "Labels",
+                "Outward issue link (Blocks)",
+                "Inward issue link (Blocks)",
+                "Custom field (Story Points)",
+            ],
+            Tracker::Linear => [
+                "ID",
+                "Title",
+                "Description",
+                "Assignee",
+                "Labels",
+                "Blocks",
+                "Blocked by",
+                "Estimate",
+            ],
+        };
+        let [key, summary, description, assignee, labels, blocks, blocked_by, points] =
+            columns.map(String::from);
+        TrackerColumns {
+            key,
+            summary,
+            description,
+            assignee,
+            labels,
+            blocks,
+            blocked_by,
+            points,
+            three_point: None,
+        }
+    }
+
+    // Overrides a column from a `field=header` pair, e.g. `points=Story point estimate`
+    pub fn set(&mut self, assignment: &str) -> Result<(), String> {
+        let (field, header) = assignment
+            .split_once('=')
+            .ok_or_else(|| format!("Expected <field>=<header>, got '{}'", assignment))?;
+        let header = header.trim().to_string();
+        let column = match field.trim() {
+            "key" => &mut self.key,
+            "summary" => &mut self.summary,
+            "description" => &mut self.description,
+            "assignee" => &mut self.assignee,
+            "labels" => &mut self.labels,
+            "blocks" => &mut self.blocks,
+            "blocked_by" => &mut self.blocked_by,
+            "points" => &mut self.points,
+            "three_point" => {
+                self.three_point = Some(header);
+                return Ok(());
+            }
+            other => {
+                return Err(format!(
+                    "Unknown field '{}'. Use key, summary, description, assignee, labels, blocks, blocked_by, points or three_point",
+                    other
+                ))
+            }
+        };
+        *column = header;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct PointsEstimate {
+    pub points: f64,
+    // Days
+    pub min: f64,
+    pub likely: f64,
+    pub max: f64,
+}
+
+// Maps story points to estimates in days. Points not in the table are not guessed at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointsTable(pub Vec<PointsEstimate>);
+
+impl Default for PointsTable {
+    fn default() -> Self {
+        PointsTable(
+            [
+                (1.0, 0.5, 1.0, 2.0),
+                (2.0, 1.0, 2.0, 4.0),
+                (3.0, 2.0, 3.0, 6.0),
+                (5.0, 3.0, 5.0, 10.0),
+                (8.0, 5.0, 8.0, 16.0),
+                (13.0, 8.0, 13.0, 26.0),
+                (21.0, 13.0, 21.0, 42.0),
+            ]
+            .into_iter()
+            .map(|(points, min, likely, max)| PointsEstimate {
+                points,
+                min,
+                likely,
+                max,
+            })
+            .collect(),
+        )
+    }
+}
+
+impl PointsTable {
+    // Reads a YAML, JSON or TOML list of `{ points, min, likely, max }` entries
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        #[derive(Deserialize)]
+        struct TableInput {
+            points: Vec<PointsEstimate>,
+        }
+
+        let format = Project::detect_format(&path)?;
+        let input: TableInput = format.deserialize(&std::fs::read_to_string(&path)?)?;
+        for entry in &input.points {
+            if !(entry.min <= entry.likely && entry.likely <= entry.max && entry.min < entry.max) {
+                return Err(format!(
+                    "Invalid estimate for {} points, expected min <= likely <= max and min < max",
+                    entry.points
+                )
+                .into());
+            }
+        }
+        Ok(PointsTable(input.points))
+    }
+
+    pub fn estimate(&self, points: f64) -> Option<&PointsEstimate> {
+        self.0.iter().find(|e| e.points == points)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackerImportOptions {
+    pub columns: TrackerColumns,
+    pub points: PointsTable,
+}
+
+impl TrackerImportOptions {
+    pub fn for_tracker(tracker: Tracker) -> Self {
+        TrackerImportOptions {
+            columns: TrackerColumns::for_tracker(tracker),
+            points: PointsTable::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnmappedIssue {
+    pub key: String,
+    pub reason: String,
+}
+
+#[derive(Debug)]
+pub struct TrackerImport {
+    pub project: Project,
+    // Issues left out of the project
+    pub unmapped: Vec<UnmappedIssue>,
+    // Links that were dropped because an end of them was left out
+    pub warnings: Vec<String>,
+}
+
+// Issue keys in a link cell, which may hold several separated by commas or spaces
+fn split_keys(cell: &str) -> impl Iterator<Item = &str> {
+    cell.split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|k| !k.is_empty())
+}
+
+fn parse_three_point(cell: &str) -> Option<EstimateInput> {
+    let values: Vec<f64> = cell
+        .split(['/', ',', ';'])
+        .map(|v| v.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    match values[..] {
+        [min, likely, max] if min <= likely && likely <= max && min < max => Some(EstimateInput {
+            min: min.into(),
+            likely: likely.into(),
+            max: max.into(),
+            unit: None,
+        }),
+        _ => None,
+    }
+}
+
+pub fn import_file<P: AsRef<Path>>(
+    path: P,
+    options: &TrackerImportOptions,
+) -> Result<TrackerImport, Box<dyn std::error::Error>> {
+    let file = std::fs::File::open(&path)?;
+    import_reader(file, path.as_ref(), options)
+}
+
+// `source` is only used to name the file in error messages
+pub fn import_reader<R: Read>(
+    reader: R,
+    source: &Path,
+    options: &TrackerImportOptions,
+) -> Result<TrackerImport, Box<dyn std::error::Error>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let headers = reader.headers()?.clone();
+    let find = |header: &str| -> Vec<usize> {
+        headers
+            .iter()
+            .enumerate()
+            .filter(|(_, h)| h.eq_ignore_ascii_case(header))
+            .map(|(i, _)| i)
+            .collect()
+    };
+    let columns = &options.columns;
+    let key_column = *find(&columns.key)
+        .first()
+        .ok_or_else(|| format!("No '{}' column in {}", columns.key, source.display()))?;
+    let [summary, description, assignee, labels, blocks, blocked_by, points] = [
+        &columns.summary,
+        &columns.description,
+        &columns.assignee,
+        &columns.labels,
+        &columns.blocks,
+        &columns.blocked_by,
+        &columns.points,
+    ]
+    .map(|header| find(header));
+    let three_point = columns.three_point.as_deref().map(find).unwrap_or_default();
+
+    let mut unmapped = Vec::new();
+    let mut warnings = Vec::new();
+    let mut inputs: Vec<TaskInput> = Vec::new();
+    // (blocker, blocked) pairs from both link columns
+    let mut links: Vec<(String, String)> = Vec::new();
+
+    for (row, record) in reader.records().enumerate() {
+        let record = record?;
+        let cells = |indices: &[usize]| -> Vec<&str> {
+            indices
+                .iter()
+                .filter_map(|&i| record.get(i))
+                .filter(|v| !v.is_empty())
+                .collect()
+        };
+        let first = |indices: &[usize]| cells(indices).first().map(|v| v.to_string());
+
+        let key = match record.get(key_column).filter(|k| !k.is_empty()) {
+            Some(key) => key.to_string(),
+            None => {
+                unmapped.push(UnmappedIssue {
+                    key: format!("row {}", row + 2),
+                    reason: format!("No {}", columns.key),
+                });
+                continue;
+            }
+        };
+        for blocked in cells(&blocks).into_iter().flat_map(split_keys) {
+            links.push((key.clone(), blocked.to_string()));
+        }
+        for blocker in cells(&blocked_by).into_iter().flat_map(split_keys) {
+            links.push((blocker.to_string(), key.clone()));
+        }
+
+        let estimate = if let Some(cell) = first(&three_point) {
+            match parse_three_point(&cell) {
+                Some(estimate) => estimate,
+                None => {
+                    unmapped.push(UnmappedIssue {
+                        key,
+                        reason: format!("Invalid three-point estimate '{}'", cell),
+                    });
+                    continue;
+                }
+            }
+        } else if let Some(cell) = first(&points) {
+            match cell.parse().ok().and_then(|p| options.points.estimate(p)) {
+                Some(e) => EstimateInput {
+                    min: e.min.into(),
+                    likely: e.likely.into(),
+                    max: e.max.into(),
+                    unit: None,
+                },
+                None => {
+                    unmapped.push(UnmappedIssue {
+                        key,
+                        reason: format!("No estimate mapped for {} points", cell),
+                    });
+                    continue;
+                }
+            }
+        } else {
+            unmapped.push(UnmappedIssue {
+                key,
+                reason: "No estimate".to_string(),
+            });
+            continue;
+        };
+
+        let mut tags: Vec<String> = Vec::new();
+        for label in cells(&labels).into_iter().flat_map(|c| c.split(',')) {
+            let label = label.trim();
+            if !label.is_empty() && !tags.iter().any(|t| t == label) {
+                tags.push(label.to_string());
+            }
+        }
+
+        inputs.push(TaskInput {
+            id: key,
+            name: first(&summary),
+            description: first(&description),
+            owner: first(&assignee),
+            tags,
+            estimate: Some(estimate),
+            ..Default::default()
+        });
+    }
+
+    let imported: HashSet<String> = inputs.iter().map(|i| i.id.clone()).collect();
+    let unmapped_keys: HashSet<&str> = unmapped.iter().map(|u| u.key.as_str()).collect();
+    for (blocker, blocked) in links {
+        match (imported.contains(&blocker), imported.contains(&blocked)) {
+            (true, true) => {
+                let input = inputs.iter_mut().find(|i| i.id == blocked).unwrap();
+                if !input.dependencies.contains(&blocker) {
+                    input.dependencies.push(blocker);
+                }
+            }
+            // Only mention links that touch an imported issue
+            (false, false) => {}
+            (blocker_imported, _) => {
+                let missing = if blocker_imported { &blocked } else { &blocker };
+                let reason = if unmapped_keys.contains(missing.as_str()) {
+                    "is unmapped"
+                } else {
+                    "is not in the export"
+                };
+                warnings.push(format!(
+                    "Dropped link {} blocks {}: {} {}",
+                    blocker, blocked, missing, reason
+                ));
+            }
+        }
+    }
+
+    let owners: HashSet<&String> = inputs.iter().filter_map(|i| i.owner.as_ref()).collect();
+    let input = ScheduleInput {
+        num_workers: Some(owners.len().max(1)),
+        workers: Vec::new(),
+        start_date: None,
+        unit: None,
+        points: None,
+        focus: None,
+        interruptions: None,
+        include: Vec::new(),
+        tasks: inputs,
+        milestones: Vec::new(),
+    };
+    let project = Project::from_input(input, source, Vec::new(), Calendar::new())?;
+
+    Ok(TrackerImport {
+        project,
+        unmapped,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tracker() {
+        assert_eq!("Jira".parse::<Tracker>(), Ok(Tracker::Jira));
+        assert_eq!("linear".parse::<Tracker>(), Ok(Tracker::Linear));
+        assert!("asana".parse::<Tracker>().is_err());
+    }
+
+    #[test]
+    fn test_set_column() {
+        let mut columns = TrackerColumns::for_tracker(Tracker::Jira);
+        columns.set("points=Story point estimate").unwrap();
+        columns.set("three_point = Estimate range").unwrap();
+        assert_eq!(columns.points, "Story point estimate");
+        assert_eq!(columns.three_point.as_deref(), Some("Estimate range"));
+        assert!(columns.set("sprint=Sprint").is_err());
+        assert!(columns.set("points").is_err());
+    }
+
+    #[test]
+    fn test_parse_three_point() {
+        let estimate = parse_three_point("2/3/5").unwrap();
+        assert_eq!(
+            (
+                estimate.min.value,
+                estimate.likely.value,
+                estimate.max.value
+            ),
+            (2.0, 3.0, 5.0)
+        );
+        assert!(parse_three_point("1, 1.5, 2").is_some());
+        assert!(parse_three_point("5/3/2").is_none());
+        assert!(parse_three_point("2/2/2").is_none());
+        assert!(parse_three_point("2/3").is_none());
+        assert!(parse_three_point("two/3/5").is_none());
+    }
+
+    #[test]
+    fn test_split_keys() {
+        let keys: Vec<_> = split_keys("ENG-1, ENG-2;ENG-3 ENG-4").collect();
+        assert_eq!(keys, vec!["ENG-1", "ENG-2", "ENG-3", "ENG-4"]);
+    }
+}
@@ -0,0 +1,308 @@
+// Units estimates are given in and results are shown in. Everything is worked
+// out in workdays: an hour is a share of the work schedule's `hours_per_day`,
+// a week is as many workdays as it has work days, and a story point is scaled
+// by the project's days-per-point estimate.
+use crate::calendar::Calendar;
+
+use serde::{Deserialize, Serialize};
+
+use std::{fmt, str::FromStr, time::Duration};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Unit {
+    Hours,
+    Days,
+    Weeks,
+    Points,
+}
+
+impl FromStr for Unit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "h" | "hour" | "hours" => Ok(Unit::Hours),
+            "d" | "day" | "days" => Ok(Unit::Days),
+            "w" | "week" | "weeks" => Ok(Unit::Weeks),
+            "pt" | "pts" | "point" | "points" => Ok(Unit::Points),
+            _ => Err(format!(
+                "Unknown unit '{}'. Use hours (h), days (d), weeks (w) or points (pt)",
+                s
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for Unit {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Unit> for String {
+    fn from(unit: Unit) -> Self {
+        unit.to_string()
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Unit::Hours => "hours",
+            Unit::Days => "days",
+            Unit::Weeks => "weeks",
+            Unit::Points => "points",
+        })
+    }
+}
+
+impl Unit {
+    fn suffix(self) -> &'static str {
+        match self {
+            Unit::Hours => "h",
+            Unit::Days => "d",
+            Unit::Weeks => "w",
+            Unit::Points => "pt",
+        }
+    }
+}
+
+// A number in an estimate, either plain or with its own unit, like `4h`
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "AmountInput", into = "AmountInput")]
+pub struct Amount {
+    pub value: f64,
+    pub unit: Option<Unit>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+enum AmountInput {
+    Number(f64),
+    Text(String),
+}
+
+impl From<f64> for Amount {
+    fn from(value: f64) -> Self {
+        Amount { value, unit: None }
+    }
+}
+
+impl FromStr for Amount {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let value = number
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid estimate '{}', expected e.g. 2, 4h or 1.5w", s))?;
+        let unit = match unit {
+            "" => None,
+            unit => Some(unit.parse()?),
+        };
+        Ok(Amount { value, unit })
+    }
+}
+
+impl TryFrom<AmountInput> for Amount {
+    type Error = String;
+
+    fn try_from(input: AmountInput) -> Result<Self, Self::Error> {
+        match input {
+            AmountInput::Number(value) => Ok(value.into()),
+            AmountInput::Text(text) => text.parse(),
+        }
+    }
+}
+
+impl From<Amount> for AmountInput {
+    fn from(amount: Amount) -> Self {
+        match amount.unit {
+            None => AmountInput::Number(amount.value),
+            Some(unit) => AmountInput::Text(format!("{}{}", amount.value, unit.suffix())),
+        }
+    }
+}
+
+// Workdays per story point, as a three point estimate
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct PointsScale {
+    pub min: f64,
+    pub likely: f64,
+    pub max: f64,
+}
+
+// Converts estimates to workdays. Plain numbers are in `unit`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EstimateUnits {
+    pub unit: Unit,
+    pub hours_per_day: f64,
+    pub days_per_week: f64,
+    pub points: Option<PointsScale>,
+}
+
+impl EstimateUnits {
+    pub fn new(calendar: &Calendar) -> Self {
+        EstimateUnits {
+            unit: Unit::Days,
+            hours_per_day: calendar.hours_per_day(),
+            days_per_week: calendar.work_days_per_week() as f64,
+            points: None,
+        }
+    }
+
+    // Workdays in `amount`, where `per_point` picks the days per point for
+    // the min, likely or max estimate it's part of
+    pub fn days(
+        &self,
+        amount: Amount,
+        unit: Option<Unit>,
+        per_point: impl Fn(&PointsScale) -> f64,
+    ) -> Result<f64, String> {
+        Ok(match amount.unit.or(unit).unwrap_or(self.unit) {
+            Unit::Hours => amount.value / self.hours_per_day,
+            Unit::Days => amount.value,
+            Unit::Weeks => amount.value * self.days_per_week,
+            Unit::Points => {
+                let scale = self
+                    .points
+                    .as_ref()
+                    .ok_or("Estimates in points need the project's `points` days per point")?;
+                amount.value * per_point(scale)
+            }
+        })
+    }
+}
+
+// The unit results are shown in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplayUnit {
+    unit: Unit,
+    // Workdays in one of the unit
+    days: f64,
+}
+
+impl Default for DisplayUnit {
+    fn default() -> Self {
+        DisplayUnit {
+            unit: Unit::Days,
+            days: 1.0,
+        }
+    }
+}
+
+impl DisplayUnit {
+    pub fn new(unit: Unit, calendar: &Calendar) -> Result<Self, String> {
+        let days = match unit {
+            Unit::Hours => 1.0 / calendar.hours_per_day(),
+            Unit::Days => 1.0,
+            Unit::Weeks => calendar.work_days_per_week() as f64,
+            Unit::Points => {
+                return Err("Results can't be shown in points, use hours, days or weeks".into())
+            }
+        };
+        Ok(DisplayUnit { unit, days })
+    }
+
+    pub fn value(&self, duration: Duration) -> f64 {
+        duration.as_secs_f64() / 86400.0 / self.days
+    }
+
+    // The working time `value` of the unit takes
+    pub fn duration(&self, value: f64) -> Duration {
+        Duration::from_secs_f64(value * self.days * 86400.0)
+    }
+
+    // Plural name, with days being working days
+    pub fn name(&self) -> &'static str {
+        match self.unit {
+            Unit::Hours => "hours",
+            Unit::Weeks => "weeks",
+            _ => "workdays",
+        }
+    }
+
+    // Capitalized name, for headings and chart axes
+    pub fn heading(&self) -> &'static str {
+        match self.unit {
+            Unit::Hours => "Hours",
+            Unit::Weeks => "Weeks",
+            _ => "Workdays",
+        }
+    }
+
+    // Decimal places for table cells
+    pub fn decimals(&self) -> usize {
+        usize::from(self.unit == Unit::Weeks)
+    }
+
+    // Short name for table cells
+    pub fn abbreviation(&self) -> &'static str {
+        match self.unit {
+            Unit::Hours => "hrs",
+            Unit::Weeks => "wks",
+            _ => "days",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn days(units: &EstimateUnits, amount: &str) -> Result<f64, String> {
+        units.days(amount.parse()?, None, |scale| scale.likely)
+    }
+
+    #[test]
+    fn test_estimate_units() {
+        let mut calendar = Calendar::new();
+        let mut units = EstimateUnits::new(&calendar);
+        assert_eq!(days(&units, "2"), Ok(2.0));
+        assert_eq!(days(&units, "4h"), Ok(0.5));
+        assert_eq!(days(&units, "1.5 w"), Ok(7.5));
+        assert_eq!(days(&units, "3 days"), Ok(3.0));
+        assert!(days(&units, "3pt").is_err());
+        assert!(days(&units, "3y").is_err());
+        assert!("h".parse::<Amount>().is_err());
+
+        units.unit = Unit::Hours;
+        units.points = Some(PointsScale {
+            min: 0.5,
+            likely: 1.0,
+            max: 2.0,
+        });
+        assert_eq!(days(&units, "12"), Ok(1.5));
+        assert_eq!(days(&units, "3pt"), Ok(3.0));
+        assert_eq!(
+            units.days(3.0.into(), Some(Unit::Points), |s| s.max),
+            Ok(6.0)
+        );
+
+        calendar.set_work_days(&[chrono::Weekday::Mon, chrono::Weekday::Tue]);
+        let units = EstimateUnits::new(&calendar);
+        assert_eq!(days(&units, "1w"), Ok(2.0));
+    }
+
+    #[test]
+    fn test_display_unit() {
+        let calendar = Calendar::new();
+        let ten_days = Duration::from_secs(10 * 86400);
+        assert_eq!(DisplayUnit::default().value(ten_days), 10.0);
+        let hours = DisplayUnit::new(Unit::Hours, &calendar).unwrap();
+        assert_eq!(hours.value(ten_days), 80.0);
+        assert_eq!(hours.name(), "hours");
+        assert_eq!(hours.heading(), "Hours");
+        let weeks = DisplayUnit::new(Unit::Weeks, &calendar).unwrap();
+        assert_eq!(weeks.value(ten_days), 2.0);
+        assert_eq!(weeks.duration(2.0), ten_days);
+        assert_eq!(hours.duration(4.0), Duration::from_secs(43200));
+        assert!(DisplayUnit::new(Unit::Points, &calendar).is_err());
+    }
+}
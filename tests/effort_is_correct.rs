@@ -30,7 +30,7 @@ fn test_average_effort_calculation() {
 
     let schedule = Project::new(tasks, num_workers, None).expect("Failed to create schedule");
 
-    let (_, effort_times) = run_multiple_simulations(&schedule, num_simulations);
+    let (_, effort_times) = run_multiple_simulations(&schedule, num_simulations).unwrap();
 
     let total_effort: Duration = effort_times.iter().sum();
     let avg_effort = total_effort / num_simulations as u32;
@@ -0,0 +1,150 @@
+use mcps::{
+    forecast::ScheduleForecast,
+    mspdi::{export, import_file, import_str, MspdiImportOptions},
+    simulation::run_detailed_simulations,
+    task::days_to_duration,
+};
+
+use chrono::NaiveDate;
+use mcps::calendar::Calendar;
+
+use std::path::Path;
+
+const PLAN: &str = "tests/fixtures/mspdi/plan.xml";
+
+// The plan has a start-to-start link with lag
+fn links_as_fs() -> MspdiImportOptions {
+    MspdiImportOptions {
+        links_as_fs: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_import_plan() {
+    let import = import_file(PLAN, &links_as_fs()).unwrap();
+    let project = import.project;
+
+    assert_eq!(project.num_workers, 2);
+    assert_eq!(project.start_date, NaiveDate::from_ymd_opt(2024, 8, 5));
+
+    let ids: Vec<_> = project.tasks.iter().map(|t| t.id.as_str()).collect();
+    assert_eq!(ids, vec!["2", "3", "5", "6"]);
+    assert_eq!(project.wbs.len(), 1);
+    assert_eq!(project.wbs[0].display_name(), "Backend");
+
+    let design = &project.tasks[0];
+    assert_eq!(design.name.as_deref(), Some("Design API"));
+    assert_eq!(
+        design.description.as_deref(),
+        Some("Agree on the payment endpoints")
+    );
+    assert_eq!(design.owner.as_deref(), Some("Alice"));
+    assert_eq!(design.parent.as_deref(), Some("1"));
+    assert_eq!(design.min_time, days_to_duration(1.5));
+    assert_eq!(design.likely_time, days_to_duration(2.0));
+    assert_eq!(design.max_time, days_to_duration(3.0));
+
+    // Depending on the milestone means depending on what it waits for
+    assert_eq!(project.milestones.len(), 1);
+    assert_eq!(project.milestones[0].display_name(), "API ready");
+    assert_eq!(project.milestones[0].dependencies, vec!["3"]);
+    let tests = &project.tasks[3];
+    assert_eq!(tests.dependencies, vec!["3", "5"]);
+    assert_eq!(tests.owner, None);
+
+    assert_eq!(
+        import.warnings,
+        vec![
+            "Treated start-to-start link from UID 2 to 'Checkout UI' (UID 5) as finish-to-start",
+            "Ignored lag on link from UID 2 to 'Checkout UI' (UID 5)",
+        ]
+    );
+}
+
+#[test]
+fn test_import_options() {
+    let options = MspdiImportOptions {
+        min_factor: 0.5,
+        max_factor: 3.0,
+        links_as_fs: true,
+    };
+    let project = import_file(PLAN, &options).unwrap().project;
+    assert_eq!(project.tasks[1].min_time, days_to_duration(2.5));
+    assert_eq!(project.tasks[1].max_time, days_to_duration(15.0));
+}
+
+#[test]
+fn test_import_rejects_other_links() {
+    let result = import_file(PLAN, &MspdiImportOptions::default());
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Can't import start-to-start link from UID 2 to 'Checkout UI' (UID 5): only \
+         finish-to-start links are supported (use --links-as-fs to treat every link as one)"
+    );
+
+    let plan = std::fs::read_to_string(PLAN).unwrap();
+    let xml = plan.replace("<Type>3</Type>", "<Type>1</Type>");
+    let result = import_str(&xml, Path::new(PLAN), &MspdiImportOptions::default());
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Can't import lag on link from UID 2 to 'Checkout UI' (UID 5): links can't have lag \
+         (use --links-as-fs to drop it)"
+    );
+
+    // Unknown link types fail even when approximating
+    let xml = plan.replace("<Type>3</Type>", "<Type>7</Type>");
+    let result = import_str(&xml, Path::new(PLAN), &links_as_fs());
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Unknown link type 7 on link from UID 2 to 'Checkout UI' (UID 5)"
+    );
+}
+
+#[test]
+fn test_import_rejects_bad_duration() {
+    let xml = std::fs::read_to_string(PLAN)
+        .unwrap()
+        .replace("PT16H0M0S", "sixteen hours");
+    let result = import_str(&xml, Path::new(PLAN), &MspdiImportOptions::default());
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Invalid duration sixteen hours"
+    );
+}
+
+#[test]
+fn test_export_round_trip() {
+    let project = import_file(PLAN, &links_as_fs()).unwrap().project;
+    let results = run_detailed_simulations(&project, 1000).unwrap();
+    let forecast = ScheduleForecast::new(&project, &results);
+    let start = project.start_date.unwrap();
+
+    let xml = export(&project, &forecast, &Calendar::new(), start).unwrap();
+
+    assert!(xml.contains("<StartDate>2024-08-05T08:00:00</StartDate>"));
+    assert!(xml.contains("<Alias>p85 Finish</Alias>"));
+    // Design API has no dependencies, so always starts on day one
+    assert!(xml.contains("<Name>Design API</Name>"));
+    assert!(xml.contains("<Start>2024-08-05T08:00:00</Start>"));
+    assert!(xml.contains("<Name>Alice</Name>"));
+
+    // Reading the export back gives the same project
+    let reimported = import_str(
+        &xml,
+        Path::new("export.xml"),
+        &MspdiImportOptions::default(),
+    )
+    .unwrap()
+    .project;
+    assert!(reimported.tasks.iter().zip(&project.tasks).all(|(a, b)| {
+        a.id == b.id
+            && a.name == b.name
+            && a.owner == b.owner
+            && a.parent == b.parent
+            && a.dependencies == b.dependencies
+            && a.likely_time == b.likely_time
+    }));
+    assert_eq!(reimported.wbs, project.wbs);
+    assert_eq!(reimported.milestones, project.milestones);
+}
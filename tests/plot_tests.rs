@@ -0,0 +1,126 @@
+use mcps::{
+    plot::{render, svg_to_png, Chart},
+    schedule::Project,
+    simulation::{run_detailed_simulations_with_options, SimulationOptions},
+    units::{DisplayUnit, Unit},
+};
+
+use resvg::tiny_skia::{Pixmap, PremultipliedColorU8};
+
+use std::path::Path;
+
+const PROJECT: &str = "tests/fixtures/plot/project.yaml";
+
+fn seeded_chart(chart: Chart) -> String {
+    seeded_chart_in(chart, DisplayUnit::default())
+}
+
+fn seeded_chart_in(chart: Chart, unit: DisplayUnit) -> String {
+    let project = Project::from_file(PROJECT).unwrap();
+    let options = SimulationOptions {
+        seed: Some(7),
+        ..Default::default()
+    };
+    let results = run_detailed_simulations_with_options(&project, 1000, &options).unwrap();
+    render(chart, &project, &results, 20, unit)
+}
+
+// Compares against the golden file, rewriting it instead when UPDATE_GOLDEN
+// is set
+fn read_golden(output: &[u8], name: &str) -> Vec<u8> {
+    let path = Path::new("tests/fixtures/plot").join(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, output).unwrap();
+    }
+    std::fs::read(path).unwrap()
+}
+
+fn assert_golden_bytes(output: &[u8], name: &str) {
+    let golden = read_golden(output, name);
+    assert!(
+        output == golden,
+        "{} differs from its golden file; rerun with UPDATE_GOLDEN=1 if the change is intended",
+        name
+    );
+}
+
+// Anti-aliasing can differ slightly between the SIMD code paths the
+// rasterizer picks on different CPUs, so images are compared pixel by pixel
+// with some tolerance rather than byte for byte
+fn assert_golden_image(png: &[u8], name: &str) {
+    let golden = Pixmap::decode_png(&read_golden(png, name)).unwrap();
+    let image = Pixmap::decode_png(png).unwrap();
+    assert_eq!(
+        (image.width(), image.height()),
+        (golden.width(), golden.height())
+    );
+
+    let channels = |p: &PremultipliedColorU8| [p.red(), p.green(), p.blue(), p.alpha()];
+    let differing = image
+        .pixels()
+        .iter()
+        .zip(golden.pixels())
+        .filter(|(a, b)| {
+            channels(a)
+                .iter()
+                .zip(channels(b))
+                .any(|(&x, y)| x.abs_diff(y) > 8)
+        })
+        .count();
+    // Allow 0.1% of the pixels to be off by more than a few shades
+    assert!(
+        differing * 1000 <= golden.pixels().len(),
+        "{} of {} pixels in {} differ from its golden file; rerun with UPDATE_GOLDEN=1 if the change is intended",
+        differing,
+        golden.pixels().len(),
+        name
+    );
+}
+
+fn assert_golden(chart: Chart, name: &str) {
+    assert_golden_bytes(seeded_chart(chart).as_bytes(), name);
+}
+
+#[test]
+fn test_cdf_golden() {
+    assert_golden(Chart::Cdf, "cdf.svg");
+}
+
+#[test]
+fn test_histogram_golden() {
+    assert_golden(Chart::Histogram, "histogram.svg");
+}
+
+#[test]
+fn test_gantt_golden() {
+    assert_golden(Chart::Gantt, "gantt.svg");
+}
+
+#[test]
+fn test_png_is_deterministic() {
+    let svg = seeded_chart(Chart::Gantt);
+    let png = svg_to_png(&svg).unwrap();
+
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    // Width and height from the IHDR chunk
+    assert_eq!(&png[16..20], 800u32.to_be_bytes());
+    assert_eq!(png, svg_to_png(&svg).unwrap());
+}
+
+#[test]
+fn test_png_golden() {
+    // The font is bundled, so the image looks the same on every machine
+    let png = svg_to_png(&seeded_chart(Chart::Gantt)).unwrap();
+    assert_golden_image(&png, "gantt.png");
+}
+
+#[test]
+fn test_charts_in_chosen_unit() {
+    let project = Project::from_file(PROJECT).unwrap();
+    let weeks = DisplayUnit::new(Unit::Weeks, &project.calendar).unwrap();
+    for chart in [Chart::Cdf, Chart::Histogram, Chart::Gantt] {
+        let svg = seeded_chart_in(chart, weeks);
+        assert!(svg.contains(">Weeks"), "{:?} axis in weeks", chart);
+        assert!(!svg.contains("Workdays"));
+    }
+}
@@ -61,7 +61,7 @@ fn test_simulation_with_different_worker_counts() {
         let schedule =
             Project::new(tasks.clone(), num_workers, None).expect("Failed to create schedule");
         let (project_durations, effort_times) =
-            run_multiple_simulations(&schedule, num_simulations);
+            run_multiple_simulations(&schedule, num_simulations).unwrap();
 
         let avg_duration = project_durations.iter().sum::<Duration>() / num_simulations as u32;
         let avg_effort = effort_times.iter().sum::<Duration>() / num_simulations as u32;
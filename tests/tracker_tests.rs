@@ -0,0 +1,92 @@
+use mcps::{
+    task::days_to_duration,
+    tracker::{import_file, PointsTable, Tracker, TrackerImportOptions, UnmappedIssue},
+};
+
+fn unmapped(key: &str, reason: &str) -> UnmappedIssue {
+    UnmappedIssue {
+        key: key.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+#[test]
+fn test_import_jira() {
+    let options = TrackerImportOptions::for_tracker(Tracker::Jira);
+    let import = import_file("tests/fixtures/tracker/jira.csv", &options).unwrap();
+    let project = import.project;
+
+    let ids: Vec<_> = project.tasks.iter().map(|t| t.id.as_str()).collect();
+    assert_eq!(ids, vec!["PAY-1", "PAY-2", "PAY-3", "PAY-4", "PAY-7"]);
+    assert_eq!(project.num_workers, 2);
+
+    let design = &project.tasks[0];
+    assert_eq!(design.name.as_deref(), Some("Design payment API"));
+    assert_eq!(
+        design.description.as_deref(),
+        Some("Agree on the endpoints")
+    );
+    assert_eq!(design.owner.as_deref(), Some("Alice"));
+    assert_eq!(design.tags, vec!["backend", "api"]);
+    assert_eq!(design.min_time, days_to_duration(2.0));
+    assert_eq!(design.likely_time, days_to_duration(3.0));
+    assert_eq!(design.max_time, days_to_duration(6.0));
+
+    // Outward links make dependents, inward links dependencies
+    assert_eq!(project.tasks[1].dependencies, vec!["PAY-1"]);
+    assert_eq!(project.tasks[3].dependencies, vec!["PAY-2", "PAY-3"]);
+    assert_eq!(project.tasks[4].dependencies, vec!["PAY-4"]);
+
+    assert_eq!(
+        import.unmapped,
+        vec![
+            unmapped("PAY-5", "No estimate"),
+            unmapped("PAY-6", "No estimate mapped for 40 points"),
+        ]
+    );
+    assert_eq!(
+        import.warnings,
+        vec![
+            "Dropped link PAY-5 blocks PAY-4: PAY-5 is unmapped",
+            "Dropped link OPS-12 blocks PAY-7: OPS-12 is not in the export",
+        ]
+    );
+}
+
+#[test]
+fn test_import_linear_with_three_point_estimates() {
+    let mut options = TrackerImportOptions::for_tracker(Tracker::Linear);
+    options.columns.set("three_point=Estimate Range").unwrap();
+    options.points = PointsTable::from_file("tests/fixtures/tracker/points.yaml").unwrap();
+    let import = import_file("tests/fixtures/tracker/linear.csv", &options).unwrap();
+    let project = import.project;
+
+    let ids: Vec<_> = project.tasks.iter().map(|t| t.id.as_str()).collect();
+    assert_eq!(ids, vec!["ENG-10", "ENG-11"]);
+    assert_eq!(project.tasks[0].tags, vec!["infra", "ci"]);
+
+    // Points come from the custom table, and the range wins over points
+    assert_eq!(project.tasks[0].likely_time, days_to_duration(1.0));
+    assert_eq!(project.tasks[1].min_time, days_to_duration(1.0));
+    assert_eq!(project.tasks[1].likely_time, days_to_duration(2.0));
+    assert_eq!(project.tasks[1].max_time, days_to_duration(4.0));
+
+    // Blocks and Blocked by describe the same link, which is kept once
+    assert_eq!(project.tasks[1].dependencies, vec!["ENG-10"]);
+
+    assert_eq!(
+        import.unmapped,
+        vec![unmapped("ENG-12", "Invalid three-point estimate '2/1/3'")]
+    );
+    assert_eq!(import.warnings.len(), 2);
+}
+
+#[test]
+fn test_missing_key_column() {
+    let options = TrackerImportOptions::for_tracker(Tracker::Linear);
+    let result = import_file("tests/fixtures/tracker/jira.csv", &options);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "No 'ID' column in tests/fixtures/tracker/jira.csv"
+    );
+}